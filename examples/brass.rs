@@ -21,7 +21,7 @@ fn main() {
     // Create audio processors
     let proc = Processors::default();
     // Build synthesis algorithm
-    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>| {
+    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>, _| {
         // Calculate the next sample for each processor
         let pink = proc.pink.step();
         let tone = proc.tone.step(440.0);
@@ -1,4 +1,4 @@
-use fon::{chan::Ch16, Audio, Frame};
+use fon::{chan::Ch16, Audio};
 use twang::next::{Synth, Wave};
 
 mod wav;
@@ -30,10 +30,9 @@ struct Processors<'a> {
 
 fn main() {
     // Load audio file.
-    let input = std::fs::read(
-        std::env::args().skip(1).next().expect("Need a PCM file"),
-    )
-    .expect("Failed to read file");
+    let input =
+        std::fs::read(std::env::args().nth(1).expect("Need a PCM file"))
+            .expect("Failed to read file");
     let mut buffer = Vec::new();
     for bytes in input.chunks(4) {
         buffer.push(f32::from_le_bytes(bytes.try_into().unwrap()));
@@ -52,9 +51,9 @@ fn main() {
     };
 
     // Build synthesis algorithm
-    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>| {
+    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>, _| {
         // Get input sample.
-        let dry = proc.input.next().unwrap_or(Default::default());
+        let dry = proc.input.next().unwrap_or_default();
 
         let mut wet = [proc.room[0].step(), proc.room[1].step()];
         let dry = [dry[Left], dry[Right]];
@@ -19,7 +19,7 @@ fn main() {
     // Create audio processors
     let proc = Processors::default();
     // Build synthesis algorithm
-    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>| {
+    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>, _| {
         // Calculate the next sample for each processor
         let tri = proc.tri.step(440.0);
         let sin = proc.sin.step(440.0);
@@ -1,6 +1,6 @@
 //! A Minor on an Electric Piano
 
-use fon::{chan::Ch16, Audio, Frame};
+use fon::{chan::Ch16, Audio};
 use twang::next::{Synth, Wave};
 
 mod wav;
@@ -83,7 +83,7 @@ fn main() {
         }
     }
     // Build synthesis algorithm
-    let mut synth = Synth::new(proc, |proc, mut frame: Frame<_, 2>| {
+    let mut synth = Synth::new(proc, |proc, mut frame: Frame<_, 2>, _| {
         for (s, pitch) in proc.piano.iter_mut().zip(PITCHES.iter()) {
             for ((i, o), v) in s.iter_mut().enumerate().zip(HARMONICS.iter()) {
                 // Get next sample from oscillator.
@@ -16,7 +16,7 @@ fn main() {
     // Create audio processors
     let proc = Processors::default();
     // Build synthesis algorithm
-    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>| {
+    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>, _| {
         // Calculate the next sample for each processor
         let noise = proc.pink.step();
         // Pan the generated audio center
@@ -0,0 +1,28 @@
+//! # Subtractive synthesis
+//! Shaping a sawtooth wave with filters to sound like a (muted) trumpet.
+
+use fon::{chan::Ch16, Audio};
+use twang::tree::{filter::FilterType, line::Line, Synth};
+
+mod wav;
+
+fn main() {
+    // Define waveform: a B♭3 sawtooth, with the highest harmonics removed and
+    // the harmonics around 1.2 kHz boosted
+    let waveform = const {
+        Line(233.08)
+            .osc()
+            .biquad(FilterType::Lowpass, Line(2_400.0), Line(0.707))
+            .biquad(FilterType::Peaking { gain: 9.0 }, Line(1_200.0), Line(1.5))
+            .biquad(FilterType::Highpass, Line(300.0), Line(0.707))
+            .clip()
+    };
+    // Initialize audio, and create synthesizer
+    let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 48_000 * 5);
+    let mut synth = Synth::new(waveform, []);
+
+    // Synthesize 5 seconds of audio
+    synth.stream(audio.sink());
+    // Write synthesized audio to WAV file
+    wav::write(audio, "trumpet.wav").expect("Failed to write WAV file");
+}
@@ -21,7 +21,7 @@ fn main() {
     // Shift sawtooth wave
     proc.saw.shift(Ch32::new(0.25));
     // Build synthesis algorithm
-    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>| {
+    let mut synth = Synth::new(proc, |proc, frame: Frame<_, 2>, _| {
         // Calculate the next sample for each processor
         let saw = proc.saw.step(440.0);
         let sin = proc.sin.step(440.0);
@@ -0,0 +1,84 @@
+// Reference impulse responses for the `reverb_reference` test in src/ops.rs
+//
+// Runs the original Freeverb `revmodel` over the same input as the test, and
+// prints the checked samples and FNV-1a hashes as Rust.  From the repository
+// root:
+//
+//     g++ -fno-strict-aliasing -ffp-contract=off -I freeverb/Components \
+//         freeverb/reference.cpp -o /tmp/freeverb-reference
+//     /tmp/freeverb-reference
+
+#include <cstdint>
+#include <cstdio>
+#include <cstring>
+
+// `revmodel.cpp` was written for compilers that keep `for` loop variables in
+// scope after the loop, so give its later loops a variable to use instead
+static int i;
+
+#include "allpass.cpp"
+#include "comb.cpp"
+#include "revmodel.cpp"
+
+// Number of samples in each impulse response
+const int length = 16384;
+// Samples checked sample-by-sample
+const int checked[] = {0, 1116, 1139, 3000, 4096, 6000, 8192, 12000, 16383};
+
+static uint32_t bits(float sample)
+{
+	uint32_t out;
+
+	memcpy(&out, &sample, sizeof(out));
+	return out;
+}
+
+static void impulse_response(revmodel &model)
+{
+	uint64_t hash = 0xcbf29ce484222325ull;
+	size_t next = 0;
+
+	for (int i = 0; i < length; i++)
+	{
+		float inputL = i == 0 ? 1.0f : 0.0f;
+		float inputR = i == 0 ? 0.5f : 0.0f;
+		float outputL, outputR;
+
+		model.processreplace(&inputL, &inputR, &outputL, &outputR, 1, 1);
+
+		if (next < sizeof(checked) / sizeof(checked[0]) && checked[next] == i)
+		{
+			printf("(%d, 0x%04x_%04x, 0x%04x_%04x),\n", i,
+				bits(outputL) >> 16, bits(outputL) & 0xffff,
+				bits(outputR) >> 16, bits(outputR) & 0xffff);
+			next++;
+		}
+
+		hash = (hash ^ bits(outputL)) * 0x100000001b3ull;
+		hash = (hash ^ bits(outputR)) * 0x100000001b3ull;
+	}
+
+	printf("hash: 0x%04x_%04x_%04x_%04x\n\n",
+		(unsigned)(hash >> 48), (unsigned)(hash >> 32) & 0xffff,
+		(unsigned)(hash >> 16) & 0xffff, (unsigned)hash & 0xffff);
+}
+
+int main()
+{
+	// Default settings
+	static revmodel defaults;
+
+	impulse_response(defaults);
+
+	// Every setting changed
+	static revmodel changed;
+
+	changed.setroomsize(0.9f);
+	changed.setdamp(0.2f);
+	changed.setwet(0.8f);
+	changed.setdry(0.5f);
+	changed.setwidth(0.5f);
+	impulse_response(changed);
+
+	return 0;
+}
@@ -1,125 +1,113 @@
 //! Twang synthesis file format
 
-#![allow(warnings)]
+#[cfg(feature = "muon")]
+mod muon;
 
-use alloc::vec::Vec;
+use alloc::{collections::VecDeque, vec, vec::Vec};
+#[cfg(feature = "muon")]
+use core::f32::consts::PI;
 use fon::chan::{Ch32, Channel};
-use fon::{Audio, Sink};
-
-/*
-/// A twang synthesis operation
-enum Op {
-    /// Pop last value on audio stack
-    Pop,
-    /// Push value onto audio stack
-    Psh(Ch32),
-    /// Swap by index
-    Swp(u32),
-    /// Swap and pop by index
-    Sap(u32),
-    /// In-place oscillator transformation (in/out: frequency)
-    Osc(Ch32),
-    /// In-place bezier transformation (in/out: source, in:curve)
-    Bez,
-}
-
-/// A synthesizer
-pub struct Synth {
-    // Audio stack
-    stack: Vec<Chunk>,
-    // Synthesis program
-    ops: Vec<Op>,
-}
-
-impl Synth {
-    /// Stream synthesized samples into a sink
-    pub fn stream(&mut self) {
-        const ONE: Ch32 = Ch32::new(1.0);
-
-        let step = (48_000.0f32).recip();
-
-        for op in self.ops.iter_mut() {
-            match op {
-                Op::Pop => { self.stack.pop(); }
-                Op::Psh(ref chan) => self.stack.push(Chunk([*chan; 32])),
-                Op::Swp(idx) => {
-                    let tmp = self.stack.swap_remove(*idx as usize);
-                    self.stack.push(tmp)
-                },
-                Op::Sap(idx) => { self.stack.swap_remove(*idx as usize); },
-                Op::Osc(ref mut time) => {
-                    let len = self.stack.len();
-                    let a = self.stack.get_mut(len - 1).unwrap();
-                    for rate in a.0.iter_mut() {
-                        let delta = *rate * step;
-                        *rate = *time;
-                        *time += delta;
-                    }
-                }
-                Op::Bez => {
-                    let len = self.stack.len();
-                    for i in 0..32 {
-                        let curve = self.stack[len - 2].0[i];
-                        let src = &mut self.stack[len - 1].0[i];
-                        if src.to_f32().is_sign_negative() {
-                            let v = *src + ONE;
-                            *src = v - v * curve * (v - ONE) - ONE;
-                        } else {
-                            let w = ONE - *src;
-                            *src = ONE - w + w * curve * (w - ONE);
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-*/
+use fon::{pos::Mono, Audio, Frame, Sink};
+
+#[cfg(feature = "muon")]
+pub use self::muon::MuonError;
+#[cfg(feature = "muon")]
+use crate::{
+    noise::{Pink, White},
+    ops::{EnvelopeState, Segment, Trigger},
+    tree::osc::sine_phase,
+};
+use crate::{
+    osc::{self, Transition, Waypoint},
+    tree::osc::{bezier, zoid},
+};
+
+/// Seconds per sample at the default sample rate of 48 kHz
+const SAMPLE_PERIOD: f32 = 1.0 / 48_000.0;
 
 /// Node in the synthesis tree
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum Node {
     Source(Chunk),
     Line(Value),
 
-    WaveT(Table, Const),
-    WaveC(Table, Chunk),
-    WaveV(Table, Value),
+    /// Wavetable oscillator
+    ///
+    /// Subtree params: tables, frequency, morph position.
+    Wave(Vec<Table>, Any, Any),
+
+    /// Waypoint oscillator
+    ///
+    /// Subtree params: table, frequency, transition.
+    Waypoint(Table, Any, Transition),
+
+    /// Bezier wave
+    ///
+    /// Subtree params: frequency, curve.
+    Bezier(Any, Any),
+
+    /// Sine wave
+    ///
+    /// Subtree params: frequency, duty, zero, peak, phase offset (in cycles).
+    #[cfg(feature = "muon")]
+    Sine(Any, Any, Any, Any, Any),
+
+    /// White noise, with a seed
+    #[cfg(feature = "muon")]
+    White(u32),
+
+    /// Pink noise, with a seed
+    #[cfg(feature = "muon")]
+    Pink(u32),
 
-    WaypointT(Table, Const),
-    WaypointC(Table, Chunk),
-    WaypointV(Table, Value),
+    /// Mixer
+    ///
+    /// Subtree params: samplers to add together.
+    #[cfg(feature = "muon")]
+    Mix(Vec<Any>),
+
+    /// Amplifier
+    ///
+    /// Subtree params: input, gain.
+    #[cfg(feature = "muon")]
+    Gain(Any, Any),
 
-    BezierTT(Const, Const),
-    BezierTC(Const, Chunk),
-    BezierTV(Const, Value),
-    BezierCT(Chunk, Const),
-    BezierCC(Chunk, Chunk),
-    BezierCV(Chunk, Value),
-    BezierVT(Value, Const),
-    BezierVC(Value, Chunk),
-    BezierVV(Value, Value),
+    /// Limiter
+    ///
+    /// Subtree params: input, ceiling, ratio, knee.
+    #[cfg(feature = "muon")]
+    Limit(Any, Any, Any, Any),
 
-    /// Frequency Counter
+    /// Clamping
     ///
-    /// A frequency counter is a sawtooth wave.
-    Freq(Ch32, u32),
+    /// Subtree params: input, minimum, maximum, shift.
+    #[cfg(feature = "muon")]
+    Clamp(Any, Any, Any, Any),
+
+    /// Amplitude envelope
+    ///
+    /// Subtree params: input, and the time (seconds) and gain of each
+    /// component, played as linear [`ops::Envelope`](crate::ops::Envelope)
+    /// segments.
+    #[cfg(feature = "muon")]
+    Envelope(Any, Vec<(Any, Any)>),
+
     /// Trapazoid wave
     ///
-    /// Subtree params: fc, rise, hold, fall.
-    Zoid(u32, u32, u32, u32),
+    /// Subtree params: fc, rise, hold, fall, phase offset (in cycles).
+    Zoid(Any, Any, Any, Any, Any),
 }
 
 /// Sample input
 ///
 /// An input is 1 value.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Value(pub u32);
 
 /// Sample chunk input / cache
 ///
 /// A chunk contains 32 samples.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Chunk(pub u32);
 
 /// Sample wavetable/waypoint input
@@ -129,7 +117,7 @@ pub struct Chunk(pub u32);
 pub struct Table(pub u32);
 
 /// Sample constant
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Const(pub Ch32);
 
 impl Sampler for Value {
@@ -157,7 +145,7 @@ mod seal {
         fn to_any(self) -> Any;
     }
 
-    #[derive(Debug, Copy, Clone)]
+    #[derive(Debug, Copy, Clone, PartialEq)]
     pub enum Any {
         Value(Value),
         Chunk(Chunk),
@@ -167,23 +155,134 @@ mod seal {
 
 use self::seal::{Any, Sampler};
 
+/// Input slots of a synth, writable between chunks
+#[derive(Debug, Default)]
+struct Inputs {
+    samples: Vec<f32>,
+    buffers: Vec<[Ch32; 32]>,
+    wtables: Vec<Vec<Ch32>>,
+}
+
+impl Inputs {
+    /// Make sure the slot for a sampler exists
+    fn reserve(&mut self, sampler: Any) {
+        match sampler {
+            Any::Value(value) => self.reserve_value(value),
+            Any::Chunk(chunk) => self.reserve_chunk(chunk),
+            Any::Const(_) => {}
+        }
+    }
+
+    fn reserve_value(&mut self, value: Value) {
+        let len = value.0 as usize + 1;
+
+        if self.samples.len() < len {
+            self.samples.resize(len, 0.0);
+        }
+    }
+
+    fn reserve_chunk(&mut self, chunk: Chunk) {
+        let len = chunk.0 as usize + 1;
+
+        if self.buffers.len() < len {
+            self.buffers.resize(len, [Ch32::default(); 32]);
+        }
+    }
+
+    fn reserve_table(&mut self, table: Table) {
+        let len = table.0 as usize + 1;
+
+        if self.wtables.len() < len {
+            self.wtables.resize(len, Vec::new());
+        }
+    }
+
+    /// Get sample `i` of the current chunk from a sampler
+    #[inline(always)]
+    fn get(&self, sampler: Any, i: usize) -> f32 {
+        match sampler {
+            Any::Value(value) => self.samples[value.0 as usize],
+            Any::Chunk(chunk) => self.buffers[chunk.0 as usize][i].to_f32(),
+            Any::Const(constant) => constant.0.to_f32(),
+        }
+    }
+
+    /// Build the mip-mapped wavetables for a [`Node::Wave`]
+    fn wavetable(&self, tables: &[Table]) -> osc::Wavetable {
+        let tables: Vec<Vec<f32>> = tables
+            .iter()
+            .map(|table| {
+                self.wtables[table.0 as usize]
+                    .iter()
+                    .map(|sample| sample.to_f32())
+                    .collect()
+            })
+            .collect();
+        let tables: Vec<&[f32]> = tables.iter().map(Vec::as_slice).collect();
+
+        osc::Wavetable::new(&tables)
+    }
+
+    /// Build the waypoints for a [`Node::Waypoint`] (evenly spaced samples)
+    fn waypoints(&self, table: Table) -> Vec<Waypoint> {
+        let samples = &self.wtables[table.0 as usize];
+        let len = samples.len() as f32;
+
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| Waypoint::new(i as f32 / len, sample.to_f32()))
+            .collect()
+    }
+}
+
 /// Builder for a synth
 ///
 /// Inputs -> Program -> Output
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct SynthBuilder {
-    nodes: Vec<Node>,
-    input_samples: Vec<f32>,
-    input_buffers: Vec<[Ch32; 32]>,
-    input_wtables: Vec<Vec<Ch32>>,
+    /// Nodes, and the chunk each one is cached in (mixed if none)
+    nodes: Vec<(Node, Option<Chunk>)>,
+    inputs: Inputs,
 }
 
 impl SynthBuilder {
+    /// Create a new synth builder, without any nodes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a synth that mixes all of the nodes added to this builder.
+    ///
+    /// All inputs start out as silence, and can be written between chunks
+    /// with [`Synth::set_value()`], [`Synth::chunk_mut()`] and
+    /// [`Synth::set_table()`].
+    pub fn build(self) -> Synth {
+        let caches = self
+            .nodes
+            .iter()
+            .map(|(node, _)| Cache::new(&self.inputs, node))
+            .collect();
+
+        Synth {
+            phases: vec![0.0; self.nodes.len()],
+            caches,
+            nodes: self.nodes,
+            inputs: self.inputs,
+            period: SAMPLE_PERIOD,
+            chunk: [0.0; 32],
+            cursor: 32,
+            feeds: Vec::new(),
+        }
+    }
+
     /// Add chunked audio from an external source
+    ///
+    /// The chunk is either written with [`Synth::chunk_mut()`] before each
+    /// [`Synth::synthesize()`], or fed with [`Synth::feed()`].
     pub fn mix_source(mut self, chunk: Chunk) -> Self {
-        self.input_buffers
-            .resize((chunk.0 + 1).try_into().unwrap(), [Ch32::default(); 32]);
-        self.nodes.push(Node::Source(chunk));
+        self.inputs.reserve_chunk(chunk);
+        self.nodes.push((Node::Source(chunk), None));
         self
     }
 
@@ -191,249 +290,658 @@ impl SynthBuilder {
     ///
     /// A line wave is a horizontal line, silence to human ears.
     pub fn mix_line(mut self, value: Value) -> Self {
-        self.input_samples
-            .resize((value.0 + 1).try_into().unwrap(), 0.0);
-        self.nodes.push(Node::Line(value));
+        self.inputs.reserve_value(value);
+        self.nodes.push((Node::Line(value), None));
         self
     }
 
     /// Add wavetable
     ///
     /// A wave table is a collection of samples that are slowed down or sped up
-    /// to make the pitch higher or lower.
-    pub fn mix_wave(mut self, table: Table, freq: impl Sampler) -> Self {
-        self.input_wtables
-            .resize((table.0 + 1).try_into().unwrap(), Vec::new());
-        self.nodes.push(match freq.to_any() {
-            Any::Value(x) => Node::WaveV(table, x),
-            Any::Chunk(x) => Node::WaveC(table, x),
-            Any::Const(x) => Node::WaveT(table, x),
-        });
+    /// to make the pitch higher or lower.  Each table is band-limited into
+    /// per-octave mip-maps (see [`osc::Wavetable`]),
+    /// and `position` morphs between them (0 is the first table, 1 is the
+    /// last).
+    pub fn mix_wave(
+        mut self,
+        tables: &[Table],
+        freq: impl Sampler,
+        position: impl Sampler,
+    ) -> Self {
+        let (freq, position) = (freq.to_any(), position.to_any());
+
+        for table in tables {
+            self.inputs.reserve_table(*table);
+        }
+        self.inputs.reserve(freq);
+        self.inputs.reserve(position);
+        self.nodes
+            .push((Node::Wave(tables.to_vec(), freq, position), None));
         self
     }
 
     /// Add waypoint input
     ///
     /// A ways table is almost the same thing as a wavetable, except allows
-    /// aliasing.
-    pub fn mix_ways(mut self, table: Table, freq: impl Sampler) -> Self {
-        self.input_wtables
-            .resize((table.0 + 1).try_into().unwrap(), Vec::new());
-        self.nodes.push(match freq.to_any() {
-            Any::Value(x) => Node::WaypointV(table, x),
-            Any::Chunk(x) => Node::WaypointC(table, x),
-            Any::Const(x) => Node::WaypointT(table, x),
-        });
+    /// aliasing.  Each sample is a waypoint, evenly spaced over the cycle,
+    /// that is either jumped to or interpolated towards depending on
+    /// `transition` (see [`osc::Waypoints`]).
+    pub fn mix_ways(
+        mut self,
+        table: Table,
+        freq: impl Sampler,
+        transition: Transition,
+    ) -> Self {
+        let freq = freq.to_any();
+
+        self.inputs.reserve_table(table);
+        self.inputs.reserve(freq);
+        self.nodes
+            .push((Node::Waypoint(table, freq, transition), None));
         self
     }
 
     /// Bezier wave
     ///
     /// A bezier wave is a waveform formed by two symmetrical bezier curves.
+    /// The curve (-1 to 1) is the same as for
+    /// [`tree::osc::Bezier`](crate::tree::osc::Bezier).
     pub fn bezier(mut self, fc: impl Sampler, speed: impl Sampler) -> Self {
-        self.nodes.push(match fc.to_any() {
-            Any::Value(x) => match speed.to_any() {
-                Any::Value(y) => Node::BezierVV(x, y),
-                Any::Chunk(y) => Node::BezierVC(x, y),
-                Any::Const(y) => Node::BezierVT(x, y),
-            },
-            Any::Chunk(x) => match speed.to_any() {
-                Any::Value(y) => Node::BezierCV(x, y),
-                Any::Chunk(y) => Node::BezierCC(x, y),
-                Any::Const(y) => Node::BezierCT(x, y),
-            },
-            Any::Const(x) => match speed.to_any() {
-                Any::Value(y) => Node::BezierTV(x, y),
-                Any::Chunk(y) => Node::BezierTC(x, y),
-                Any::Const(y) => Node::BezierTT(x, y),
-            },
-        });
+        let (fc, speed) = (fc.to_any(), speed.to_any());
+
+        self.inputs.reserve(fc);
+        self.inputs.reserve(speed);
+        self.nodes.push((Node::Bezier(fc, speed), None));
+        self
+    }
+
+    /// Trapazoid wave
+    ///
+    /// A trapazoid wave rises, holds, falls and holds again each cycle, from
+    /// locations in the cycle (-1 to 1) that are the same as for
+    /// [`tree::osc::Zoid`](crate::tree::osc::Zoid).
+    pub fn zoid(
+        mut self,
+        fc: impl Sampler,
+        rise: impl Sampler,
+        hold: impl Sampler,
+        fall: impl Sampler,
+    ) -> Self {
+        let params = [fc.to_any(), rise.to_any(), hold.to_any(), fall.to_any()];
+
+        for param in params {
+            self.inputs.reserve(param);
+        }
+
+        let [fc, rise, hold, fall] = params;
+        let offset = Const(Ch32::new(0.0)).to_any();
+
+        self.nodes
+            .push((Node::Zoid(fc, rise, hold, fall, offset), None));
         self
     }
 }
 
-/*
+/// Data derived from the input tables for a node
 #[derive(Debug)]
-struct EnvelopeComponent {
-    time: i32,
-    gain: i32,
+enum Cache {
+    None,
+    Wavetable(osc::Wavetable),
+    Waypoints(Vec<Waypoint>),
+    #[cfg(feature = "muon")]
+    White(White),
+    #[cfg(feature = "muon")]
+    Pink(Pink),
+    /// Playback state, and the segments built from the inputs each sample
+    #[cfg(feature = "muon")]
+    Envelope(EnvelopeState, Vec<Segment>),
 }
 
-#[derive(Debug)]
-enum Function {
-    Sine {
-        hz: i32,
-        duty: Option<i32>,
-        zero: Option<i32>,
-        peak: Option<i32>,
-    },
-    White {
-        seed: Option<u32>,
-    },
-    Pink {
-        seed: Option<u32>,
-    },
-    Phase {
-        func: i32,
-        offset: i32,
-    },
-    Line(f32),
-    Mix {
-        func: i32,
-        amt: i32,
-    },
-    Limit {
-        func: i32,
-        ceil: Option<i32>,
-        ratio: Option<i32>,
-        knee: Option<i32>,
-    },
-    Clamp {
-        func: i32,
-        min: Option<i32>,
-        max: Option<i32>,
-        shift: Option<i32>,
-    },
-    Envelope {
-        func: i32,
-        with: Vec<EnvelopeComponent>,
-    },
-    Reverb,
-    Shape,
-    Table,
+impl Cache {
+    fn new(inputs: &Inputs, node: &Node) -> Self {
+        match node {
+            Node::Wave(tables, _, _) => {
+                Self::Wavetable(inputs.wavetable(tables))
+            }
+            Node::Waypoint(table, _, _) => {
+                Self::Waypoints(inputs.waypoints(*table))
+            }
+            #[cfg(feature = "muon")]
+            Node::White(seed) => Self::White(White::with_seed(*seed)),
+            #[cfg(feature = "muon")]
+            Node::Pink(seed) => Self::Pink(Pink::with_seed(*seed)),
+            #[cfg(feature = "muon")]
+            Node::Envelope(_, components) => Self::Envelope(
+                EnvelopeState::default(),
+                Vec::with_capacity(components.len()),
+            ),
+            _ => Self::None,
+        }
+    }
 }
 
-#[derive(Debug)]
-struct Sampler {
-    // Input samplers
-    args: i32,
-    func: Function,
+/// Offset a phase (0 to 1) by a number of cycles
+fn shift(phase: f32, offset: f32) -> f32 {
+    let shifted = (phase + offset) % 1.0;
+
+    if shifted < 0.0 {
+        shifted + 1.0
+    } else {
+        shifted
+    }
 }
 
-/// A Twang Synthesizer Instance
+/// Limit the amplitude of a sample above a ceiling
+///
+/// Above the ceiling, the gain (slope) moves from 1 to `ratio` over the
+/// `knee` (amplitude above the ceiling), and stays at `ratio` after.
+#[cfg(feature = "muon")]
+fn limit(sample: f32, ceil: f32, ratio: f32, knee: f32) -> f32 {
+    let over = sample.abs() - ceil;
+
+    if over <= 0.0 {
+        return sample;
+    }
+
+    let reduced = if over < knee {
+        over - (1.0 - ratio) * over * over / (2.0 * knee)
+    } else {
+        knee - (1.0 - ratio) * knee / 2.0 + (over - knee) * ratio
+    };
+
+    libm::copysignf(ceil + reduced, sample)
+}
+
+/// A synth built with [`SynthBuilder`]
+///
+/// Synthesizes audio in chunks of 32 samples, mixing all of the nodes.
+///
+/// ```rust
+/// use fon::chan::Ch32;
+/// use twang::file::{Const, SynthBuilder, Value};
+///
+/// let mut synth = SynthBuilder::new()
+///     .bezier(Value(0), Const(Ch32::new(0.0)))
+///     .build();
+///
+/// // Play 440 hertz for the first chunk, and 220 hertz for the next
+/// synth.set_value(Value(0), 440.0);
+/// let first = synth.synthesize();
+/// synth.set_value(Value(0), 220.0);
+/// let second = synth.synthesize();
+/// ```
 #[derive(Debug)]
 pub struct Synth {
-    def: Vec<Sampler>,
-    synth: i32,
-    buffer: Vec<Ch32>,
+    /// Nodes, and the chunk each one is cached in (mixed if none)
+    nodes: Vec<(Node, Option<Chunk>)>,
+    inputs: Inputs,
+    /// Oscillator phase of each node (0 to 1)
+    phases: Vec<f32>,
+    /// Data derived from the input tables for each node
+    caches: Vec<Cache>,
+    /// Seconds per sample
+    period: f32,
+    /// Chunk being streamed
+    chunk: [f32; 32],
+    /// Index of the next sample to stream from the chunk
+    cursor: usize,
+    /// Audio fed into chunk inputs, not yet synthesized
+    feeds: Vec<(Chunk, VecDeque<f32>)>,
 }
 
 impl Synth {
-    /// Get a builder to construct this synth
-    pub fn builder() -> SynthBuilder {
-        SynthBuilder::new()
-    }
-
-    /// Stream synthesized samples into a [`Sink`].
-    pub fn stream(&mut self, sink: impl Sink<Ch32, 1>) {
-        sink.sink_with(core::iter::from_fn(|| {
-            if self.buffer.is_empty() {
-                self.buffer.pop()
-            } else {
-                self.buffer.pop()
+    /// Set a [`Value`] input.
+    ///
+    /// # Panics
+    ///
+    /// If the value isn't used by any node.
+    pub fn set_value(&mut self, value: Value, sample: f32) {
+        self.inputs.samples[value.0 as usize] = sample;
+    }
+
+    /// Get a mutable reference to a [`Chunk`] input, to write the next 32
+    /// samples into.
+    ///
+    /// # Panics
+    ///
+    /// If the chunk isn't used by any node.
+    pub fn chunk_mut(&mut self, chunk: Chunk) -> &mut [Ch32; 32] {
+        &mut self.inputs.buffers[chunk.0 as usize]
+    }
+
+    /// Replace the samples of a [`Table`] input.
+    ///
+    /// Wavetables using the table are mip-mapped again, so this can be slow.
+    ///
+    /// # Panics
+    ///
+    /// If the table isn't used by any node.
+    pub fn set_table(&mut self, table: Table, samples: impl Into<Vec<Ch32>>) {
+        self.inputs.wtables[table.0 as usize] = samples.into();
+
+        let nodes = self.nodes.iter().zip(self.caches.iter_mut());
+
+        for ((node, _), cache) in nodes {
+            let uses = match node {
+                Node::Wave(tables, _, _) => {
+                    tables.iter().any(|t| t.0 == table.0)
+                }
+                Node::Waypoint(t, _, _) => t.0 == table.0,
+                _ => false,
+            };
+
+            if uses {
+                *cache = Cache::new(&self.inputs, node);
             }
-        }));
+        }
+    }
+
+    /// Change the sample rate (in Hz) this synth generates audio for.
+    ///
+    /// Defaults to 48 kHz, and is set by [`Synth::stream()`].
+    ///
+    /// # Panics
+    /// If `sample_rate` is 0.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.period = crate::math::period(sample_rate);
     }
 
-    /// Apply a function to a chunk of audio.
-    fn apply(&self, chunk: &mut [Ch32; 32], f: Function) {
-        use Function::*;
-        match f {
-            Zoid {
-                hz,
-                rise,
-                hold,
-                fall,
-            } => {
-                todo!()
-            },
-            Sine {
-                hz,
-                duty,
-                zero,
-                peak,
-            } => {
-                todo!()
-            },
-            White {
-                seed,
-            } => {
-                todo!()
-            },
-            Pink {
-                seed,
-            } => {
-                todo!()
-            },
-            Phase {
-                func,
-                offset,
-            } => {
-                todo!()
-            },
-            Line(value) => {
-                for sample in chunk.iter_mut() {
-                    *sample = value.into();
+    /// Feed audio (at the sample rate of the synth) into a [`Chunk`] input,
+    /// after any audio fed before.
+    ///
+    /// Once fed, the next 32 samples are copied into the chunk before each
+    /// [`Synth::synthesize()`] (silence when the fed audio runs out), for
+    /// processing live or recorded audio with [`SynthBuilder::mix_source()`].
+    /// Audio with more channels is mixed down to mono.
+    ///
+    /// ```rust
+    /// use fon::{chan::Ch32, Audio};
+    /// use twang::file::{Chunk, SynthBuilder};
+    ///
+    /// let mut synth = SynthBuilder::new().mix_source(Chunk(0)).build();
+    /// let recording = Audio::<Ch32, 1>::with_silence(48_000, 480);
+    /// let mut output = Audio::<Ch32, 1>::with_silence(48_000, 480);
+    ///
+    /// synth.feed(Chunk(0), &recording);
+    /// synth.stream(output.sink());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the chunk isn't used by any node.
+    pub fn feed<Ch, const CH: usize>(
+        &mut self,
+        chunk: Chunk,
+        audio: &Audio<Ch, CH>,
+    ) where
+        Ch: Channel,
+        Ch32: From<Ch>,
+    {
+        assert!(
+            (chunk.0 as usize) < self.inputs.buffers.len(),
+            "Chunk isn't used by any node"
+        );
+
+        let samples = audio
+            .iter()
+            .map(|frame| frame.to::<Ch32, 1>()[Mono].to_f32());
+
+        match self.feeds.iter_mut().find(|(fed, _)| *fed == chunk) {
+            Some((_, queue)) => queue.extend(samples),
+            None => self.feeds.push((chunk, samples.collect())),
+        }
+    }
+
+    /// Synthesize the next chunk of 32 samples.
+    pub fn synthesize(&mut self) -> [Ch32; 32] {
+        for (chunk, queue) in self.feeds.iter_mut() {
+            let buffer = &mut self.inputs.buffers[chunk.0 as usize];
+            let len = queue.len().min(32);
+
+            *buffer = [Ch32::new(0.0); 32];
+            for (sample, fed) in buffer.iter_mut().zip(queue.drain(..len)) {
+                *sample = Ch32::new(fed);
+            }
+        }
+
+        let mut mixed = [0.0; 32];
+        let period = self.period;
+        let nodes = self.nodes.iter().zip(self.caches.iter_mut());
+
+        for (((node, dest), cache), phase) in nodes.zip(self.phases.iter_mut())
+        {
+            let mut out = [0.0; 32];
+            let inputs = &self.inputs;
+
+            match (node, cache) {
+                (Node::Source(chunk), _) => {
+                    let buffer = &inputs.buffers[chunk.0 as usize];
+
+                    for (out, sample) in out.iter_mut().zip(buffer) {
+                        *out += sample.to_f32();
+                    }
+                }
+                (Node::Line(value), _) => {
+                    let sample = inputs.samples[value.0 as usize];
+
+                    for out in out.iter_mut() {
+                        *out += sample;
+                    }
+                }
+                (Node::Wave(_, freq, position), Cache::Wavetable(table)) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        let dt = inputs.get(*freq, i) * period;
+
+                        *out +=
+                            table.sample(*phase, dt, inputs.get(*position, i));
+                        *phase = (*phase + dt).rem_euclid(1.0);
+                    }
                 }
-            },
-            Mix {
-                func,
-                amt,
-            } => {
-                todo!()
-            },
-            Limit {
-                func,
-                ceil,
-                ratio,
-                knee,
-            } => {
-                todo!()
-            },
-            Clamp {
-                func,
-                min,
-                max,
-                shift,
-            } => {
-                todo!()
-            },
-            Envelope {
-                func,
-                with,
-            } => {
-                todo!()
-            },
-            Reverb => todo!(),
-            Shape => todo!(),
-            Table => todo!(),
+                (
+                    Node::Waypoint(_, freq, transition),
+                    Cache::Waypoints(points),
+                ) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        *out += osc::waypoints(points, *transition, *phase);
+                        *phase = (*phase + inputs.get(*freq, i) * period)
+                            .rem_euclid(1.0);
+                    }
+                }
+                (Node::Bezier(fc, curve), _) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        // Same phase (1 to -1) as `tree::osc::Osc`
+                        *out +=
+                            bezier(1.0 - 2.0 * *phase, inputs.get(*curve, i));
+                        *phase = (*phase + inputs.get(*fc, i) * period) % 1.0;
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (Node::Sine(fc, duty, zero, peak, offset), _) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        let shifted = shift(*phase, inputs.get(*offset, i));
+                        let shaped = sine_phase(
+                            1.0 - 2.0 * shifted,
+                            inputs.get(*duty, i),
+                            inputs.get(*zero, i),
+                            inputs.get(*peak, i),
+                        );
+
+                        // Same as `tree::osc::ShapedSine`
+                        *out += -libm::cosf(PI * shaped);
+                        *phase = (*phase + inputs.get(*fc, i) * period) % 1.0;
+                    }
+                }
+                (Node::Zoid(fc, rise, hold, fall, offset), _) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        let shifted = shift(*phase, inputs.get(*offset, i));
+
+                        *out += zoid(
+                            2.0 * shifted - 1.0,
+                            inputs.get(*rise, i),
+                            inputs.get(*hold, i),
+                            inputs.get(*fall, i),
+                        );
+                        *phase = (*phase + inputs.get(*fc, i) * period) % 1.0;
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (Node::White(_), Cache::White(white)) => {
+                    for out in out.iter_mut() {
+                        *out += white.step().to_f32();
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (Node::Pink(_), Cache::Pink(pink)) => {
+                    for out in out.iter_mut() {
+                        *out += pink.step().to_f32();
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (Node::Mix(samplers), _) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        for sampler in samplers {
+                            *out += inputs.get(*sampler, i);
+                        }
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (Node::Gain(input, amt), _) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        *out += inputs.get(*input, i) * inputs.get(*amt, i);
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (Node::Limit(input, ceil, ratio, knee), _) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        *out += limit(
+                            inputs.get(*input, i),
+                            inputs.get(*ceil, i),
+                            inputs.get(*ratio, i),
+                            inputs.get(*knee, i),
+                        );
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (Node::Clamp(input, min, max, shift), _) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        let (min, max) =
+                            (inputs.get(*min, i), inputs.get(*max, i));
+
+                        *out += inputs.get(*input, i).max(min).min(max)
+                            + inputs.get(*shift, i);
+                    }
+                }
+                #[cfg(feature = "muon")]
+                (
+                    Node::Envelope(input, components),
+                    Cache::Envelope(state, segments),
+                ) => {
+                    for (i, out) in out.iter_mut().enumerate() {
+                        segments.clear();
+                        segments.extend(components.iter().map(|(t, g)| {
+                            Segment::linear(
+                                inputs.get(*t, i),
+                                inputs.get(*g, i),
+                            )
+                        }));
+
+                        // Gate stays open from when the synth starts, holding
+                        // the last gain
+                        *out += inputs.get(*input, i)
+                            * state.step(
+                                segments,
+                                &[],
+                                Trigger::Retrigger,
+                                1.0,
+                                period,
+                            );
+                    }
+                }
+                // Cache is built to match each node
+                (Node::Wave(..) | Node::Waypoint(..), _) => unreachable!(),
+                #[cfg(feature = "muon")]
+                (Node::White(_) | Node::Pink(_) | Node::Envelope(..), _) => {
+                    unreachable!()
+                }
+            }
+
+            match dest {
+                Some(chunk) => {
+                    self.inputs.buffers[chunk.0 as usize] = out.map(Ch32::from);
+                }
+                None => {
+                    for (mixed, out) in mixed.iter_mut().zip(out) {
+                        *mixed += out;
+                    }
+                }
+            }
         }
+
+        mixed.map(Ch32::from)
     }
-}
 
-/// Builder for [`Synth`]
-#[derive(Debug)]
-pub struct SynthBuilder {
-    synth: Synth,
+    /// Stream synthesized samples into a [`Sink`], setting the sample rate.
+    ///
+    /// Inputs stay the same for the whole stream, except for chunks fed with
+    /// [`Synth::feed()`].
+    pub fn stream<Ch, const S: usize>(&mut self, mut sink: impl Sink<Ch, S>)
+    where
+        Ch: Channel + From<Ch32>,
+    {
+        self.set_sample_rate(sink.sample_rate().get());
+        sink.sink_with(&mut core::iter::from_fn(|| {
+            if self.cursor == 32 {
+                self.chunk = self.synthesize().map(Ch32::to_f32);
+                self.cursor = 0;
+            }
+
+            let sample = self.chunk[self.cursor];
+
+            self.cursor += 1;
+            Some(Frame::<Ch32, 1>::new(sample.into()).to())
+        }));
+    }
 }
 
-impl SynthBuilder {
-    fn new() -> Self {
-        let synth = Synth {
-            def: Vec::new(),
-            synth: i32::MIN,
-            buffer: [Ch32::new(0.0); 32],
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Self { synth }
+    #[test]
+    fn mix_inputs() {
+        let mut synth = SynthBuilder::new()
+            .mix_line(Value(0))
+            .mix_source(Chunk(1))
+            .build();
+
+        for chunk in 0..3 {
+            let line = chunk as f32 * 0.25;
+
+            synth.set_value(Value(0), line);
+            for (i, sample) in synth.chunk_mut(Chunk(1)).iter_mut().enumerate()
+            {
+                *sample = Ch32::new(i as f32 / 32.0);
+            }
+
+            for (i, sample) in synth.synthesize().iter().enumerate() {
+                assert_eq!(sample.to_f32(), line + i as f32 / 32.0);
+            }
+        }
     }
 
-    /// Create a line wave (constant, makes no sound)
-    pub fn line(mut self, value: impl Into<Ch32>) -> Self {
-        self.synth.def.push(Function::Line(value.into().to_f32()));
-        self
+    #[test]
+    fn oscillators_match_osc() {
+        let square: Vec<Ch32> = (0..64)
+            .map(|i| Ch32::new(if i < 32 { 1.0 } else { -1.0 }))
+            .collect();
+        let saw: Vec<Ch32> =
+            (0..64).map(|i| Ch32::new(1.0 - i as f32 / 32.0)).collect();
+        let steps = [1.0, 0.5, -0.5, -1.0].map(Ch32::new);
+        let mut synth = SynthBuilder::new()
+            .mix_wave(&[Table(0), Table(1)], Value(0), Const(Ch32::new(0.25)))
+            .build();
+        let mut ways = SynthBuilder::new()
+            .mix_ways(Table(0), Value(0), Transition::Linear)
+            .build();
+
+        synth.set_table(Table(0), square.clone());
+        synth.set_table(Table(1), saw.clone());
+        synth.set_value(Value(0), 3_000.0);
+        ways.set_table(Table(0), steps);
+        ways.set_value(Value(0), 3_000.0);
+
+        let square: Vec<f32> = square.iter().map(|x| x.to_f32()).collect();
+        let saw: Vec<f32> = saw.iter().map(|x| x.to_f32()).collect();
+        let wavetable = osc::Wavetable::new(&[&square, &saw]);
+        let mut table = osc::TableOsc::new(&wavetable);
+        let points = [
+            Waypoint::new(0.0, 1.0),
+            Waypoint::new(0.25, 0.5),
+            Waypoint::new(0.5, -0.5),
+            Waypoint::new(0.75, -1.0),
+        ];
+        let mut waypoints = osc::Waypoints::new(&points, Transition::Linear);
+
+        for _ in 0..100 {
+            for (a, b) in synth.synthesize().into_iter().zip(ways.synthesize())
+            {
+                let expected_a = table.step(3_000.0, Ch32::new(0.25));
+                let expected_b = waypoints.step(3_000.0);
+
+                assert_eq!(a, expected_a);
+                assert_eq!(b, expected_b);
+            }
+        }
     }
 
+    #[test]
+    fn zoid_matches_tree() {
+        use crate::tree::{line::Line, Synth as Tree};
 
+        let mut synth = SynthBuilder::new()
+            .zoid(
+                Const(Ch32::new(440.0)),
+                Const(Ch32::new(-0.5)),
+                Value(0),
+                Const(Ch32::new(0.5)),
+            )
+            .build();
+        let mut audio = Audio::<Ch32, 1>::with_silence(48_000, 4_800);
+        let wave = Line(440.0).osc().zoid(Line(-0.5), Line(0.0), Line(0.5));
+
+        Tree::new(wave, []).stream(audio.sink());
+        synth.set_value(Value(0), 0.0);
+
+        let samples = (0..150).flat_map(|_| synth.synthesize());
+
+        for (a, b) in samples.zip(audio.as_f32_slice().iter()) {
+            assert!((a.to_f32() - *b).abs() < 0.001, "{a:?} != {b}");
+        }
+    }
+
+    #[test]
+    fn stream_sample_rate() {
+        for rate in [44_100, 96_000] {
+            let mut synth = SynthBuilder::new()
+                .bezier(Const(Ch32::new(440.0)), Const(Ch32::new(0.0)))
+                .build();
+            let mut audio = Audio::<Ch32, 1>::with_silence(rate, rate as usize);
+
+            synth.stream(audio.sink());
+
+            // Rising zero crossings (one per cycle of a falling sawtooth)
+            let cycles = audio
+                .iter()
+                .zip(audio.iter().skip(1))
+                .filter(|(a, b)| {
+                    a[Mono].to_f32() < 0.0 && b[Mono].to_f32() >= 0.0
+                })
+                .count();
+
+            assert!((439..=441).contains(&cycles), "{rate}: {cycles}");
+        }
+    }
+
+    #[test]
+    fn feed_source() {
+        let mut synth = SynthBuilder::new().mix_source(Chunk(0)).build();
+        let recording = |range: core::ops::Range<u32>| {
+            let samples: Vec<f32> = range.map(|i| i as f32 / 100.0).collect();
+
+            Audio::<Ch32, 1>::with_f32_buffer(48_000, samples)
+        };
+        let mut audio = Audio::<Ch32, 1>::with_silence(48_000, 120);
+
+        // Fed audio follows on from earlier audio
+        synth.feed(Chunk(0), &recording(0..50));
+        synth.feed(Chunk(0), &recording(50..100));
+        synth.stream(audio.sink());
+
+        // Played back once, unchanged, then silence
+        assert_eq!(
+            audio.as_f32_slice()[..100],
+            recording(0..100).as_f32_slice()[..]
+        );
+        assert_eq!(audio.as_f32_slice()[100..], [0.0; 20]);
+    }
 }
-*/
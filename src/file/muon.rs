@@ -0,0 +1,1466 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::{Display, Error, Formatter};
+
+use fon::chan::Ch32;
+
+use super::{Any, Chunk, Const, Node, Synth, SynthBuilder};
+
+/// An error loading a synth from the MuON twang synthesis language
+///
+/// Lines are numbered from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MuonError {
+    /// Line isn't indented by 2 more spaces than the line it's under
+    Indent {
+        /// Line number
+        line: usize,
+    },
+    /// Line isn't a `key: value` pair, or a name is invalid
+    Syntax {
+        /// Line number
+        line: usize,
+    },
+    /// Unknown key, or key in the wrong place
+    Key {
+        /// Line number
+        line: usize,
+    },
+    /// Key, argument or sampler name appears more than once
+    Duplicate {
+        /// Line number of the repeat
+        line: usize,
+    },
+    /// Record is missing a required field
+    Missing {
+        /// Line number of the record
+        line: usize,
+        /// Name of the missing field
+        field: &'static str,
+    },
+    /// Unknown sampler function
+    Function {
+        /// Line number
+        line: usize,
+    },
+    /// Sampler function or field isn't supported yet
+    Unsupported {
+        /// Line number
+        line: usize,
+    },
+    /// Invalid number
+    Number {
+        /// Line number
+        line: usize,
+    },
+    /// Invalid sampler expression (unbalanced parentheses, or a group of
+    /// samplers that isn't passed to a call)
+    Expression {
+        /// Line number
+        line: usize,
+    },
+    /// Name isn't an argument or a defined sampler
+    Unknown {
+        /// Line number of the reference
+        line: usize,
+        /// The unknown name
+        name: String,
+    },
+    /// Sampler is called with the wrong number of arguments
+    Arguments {
+        /// Line number of the call
+        line: usize,
+        /// Name of the sampler
+        name: String,
+    },
+    /// Sampler depends on itself
+    Cycle {
+        /// Line number of the reference that completes the cycle
+        line: usize,
+        /// Name of the sampler
+        name: String,
+    },
+    /// Phase offset isn't applied to an oscillator
+    Phase {
+        /// Line number of the function to offset
+        line: usize,
+    },
+    /// Document has no `synth` to stream audio from
+    NoSynth,
+}
+
+impl Display for MuonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        use MuonError::*;
+        match self {
+            Indent { line } => write!(f, "invalid indentation on line {line}"),
+            Syntax { line } => write!(f, "invalid syntax on line {line}"),
+            Key { line } => write!(f, "unexpected key on line {line}"),
+            Duplicate { line } => write!(f, "duplicate name on line {line}"),
+            Missing { line, field } => {
+                write!(f, "missing field `{field}` for line {line}")
+            }
+            Function { line } => write!(f, "unknown function on line {line}"),
+            Unsupported { line } => {
+                write!(f, "unsupported function or field on line {line}")
+            }
+            Number { line } => write!(f, "invalid number on line {line}"),
+            Expression { line } => {
+                write!(f, "invalid expression on line {line}")
+            }
+            Unknown { line, name } => {
+                write!(f, "unknown sampler `{name}` on line {line}")
+            }
+            Arguments { line, name } => {
+                write!(
+                    f,
+                    "wrong number of arguments to `{name}` on line {line}"
+                )
+            }
+            Cycle { line, name } => {
+                write!(f, "sampler `{name}` depends on itself on line {line}")
+            }
+            Phase { line } => {
+                write!(f, "phase offset of non-oscillator on line {line}")
+            }
+            NoSynth => write!(f, "missing `synth` to stream audio from"),
+        }
+    }
+}
+
+/// A `key: value` line, and the lines indented under it
+#[derive(Debug)]
+struct Item<'a> {
+    line: usize,
+    key: &'a str,
+    value: &'a str,
+    items: Vec<Item<'a>>,
+}
+
+/// Move the items of the deepest level under the last item of the level above
+fn fold(levels: &mut Vec<Vec<Item<'_>>>) {
+    let items = levels.pop().unwrap_or_default();
+
+    if let Some(parent) = levels.last_mut().and_then(|level| level.last_mut()) {
+        parent.items = items;
+    }
+}
+
+/// Split a document into items, skipping comments and the schema
+fn items(text: &str) -> Result<Vec<Item<'_>>, MuonError> {
+    // Items at each level of indentation, under the last item of the level
+    // above
+    let mut levels: Vec<Vec<Item<'_>>> = vec![Vec::new()];
+    let mut schema = false;
+
+    for (line, text) in (1..).zip(text.lines()) {
+        let trimmed = text.trim();
+
+        if trimmed == ":::" {
+            schema = !schema;
+            continue;
+        }
+        if schema || trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let content = text.trim_start_matches(' ');
+        let indent = text.len() - content.len();
+        let level = indent / 2;
+
+        if indent % 2 != 0
+            || content.starts_with(char::is_whitespace)
+            || level > levels.len()
+            || (level == levels.len() && levels[level - 1].is_empty())
+        {
+            return Err(MuonError::Indent { line });
+        }
+        while levels.len() > level + 1 {
+            fold(&mut levels);
+        }
+        if level == levels.len() {
+            levels.push(Vec::new());
+        }
+
+        let (key, value) =
+            content.split_once(':').ok_or(MuonError::Syntax { line })?;
+
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(MuonError::Syntax { line });
+        }
+        levels[level].push(Item {
+            line,
+            key,
+            value: value.trim(),
+            items: Vec::new(),
+        });
+    }
+
+    while levels.len() > 1 {
+        fold(&mut levels);
+    }
+
+    Ok(levels.pop().unwrap_or_default())
+}
+
+/// Check if a word is a valid sampler or argument name
+fn is_name(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && word.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Sampler expression, in the postfix calling convention
+///
+/// Each variant starts with its line number.
+#[derive(Debug)]
+enum Expr<'a> {
+    /// Constant
+    Number(usize, f32),
+    /// Argument, or sampler without arguments
+    Name(usize, &'a str),
+    /// Sampler called with arguments: `(a b).Name`
+    Call(usize, &'a str, Vec<Expr<'a>>),
+}
+
+impl Expr<'_> {
+    fn line(&self) -> usize {
+        match *self {
+            Self::Number(line, _)
+            | Self::Name(line, _)
+            | Self::Call(line, _, _) => line,
+        }
+    }
+}
+
+/// Expression token
+#[derive(Debug, Copy, Clone)]
+enum Token<'a> {
+    /// `(`
+    Open,
+    /// `)`
+    Close,
+    /// Name or number
+    Word(&'a str),
+    /// `.Name`
+    Call(&'a str),
+}
+
+/// Split an expression into tokens
+fn tokens(line: usize, mut text: &str) -> Result<Vec<Token<'_>>, MuonError> {
+    let mut tokens = Vec::new();
+
+    loop {
+        text = text.trim_start();
+
+        let len = match text.chars().next() {
+            None => return Ok(tokens),
+            Some('(') => {
+                tokens.push(Token::Open);
+                1
+            }
+            Some(')') => {
+                tokens.push(Token::Close);
+                1
+            }
+            Some(_) => {
+                let end = text
+                    .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .unwrap_or(text.len());
+                let word = &text[..end];
+                // Calls start at a `.` followed by a name (not a decimal
+                // point)
+                let calls = word
+                    .match_indices('.')
+                    .map(|(i, _)| i)
+                    .find(|i| {
+                        word[i + 1..].starts_with(|c: char| {
+                            c.is_alphabetic() || c == '_'
+                        })
+                    })
+                    .unwrap_or(end);
+
+                if calls > 0 {
+                    tokens.push(Token::Word(&word[..calls]));
+                }
+                for name in word[calls..].split('.').skip(1) {
+                    if !is_name(name) {
+                        return Err(MuonError::Expression { line });
+                    }
+                    tokens.push(Token::Call(name));
+                }
+                end
+            }
+        };
+
+        text = &text[len..];
+    }
+}
+
+/// Recursive descent expression parser
+struct Parser<'a> {
+    line: usize,
+    tokens: Vec<Token<'a>>,
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+
+        self.index += 1;
+        token
+    }
+
+    /// Parse expressions until a `)` or the end
+    fn sequence(&mut self) -> Result<Vec<Expr<'a>>, MuonError> {
+        let mut exprs = Vec::new();
+
+        while !matches!(self.peek(), None | Some(Token::Close)) {
+            exprs.push(self.postfix()?);
+        }
+
+        Ok(exprs)
+    }
+
+    /// Parse a name, number or group, followed by any number of calls
+    fn postfix(&mut self) -> Result<Expr<'a>, MuonError> {
+        let line = self.line;
+        let mut args = match self.next() {
+            Some(Token::Word(word)) if is_name(word) => {
+                vec![Expr::Name(line, word)]
+            }
+            Some(Token::Word(word)) => {
+                let number =
+                    word.parse().map_err(|_| MuonError::Number { line })?;
+
+                vec![Expr::Number(line, number)]
+            }
+            Some(Token::Open) => {
+                let args = self.sequence()?;
+
+                match self.next() {
+                    Some(Token::Close) => args,
+                    _ => return Err(MuonError::Expression { line }),
+                }
+            }
+            _ => return Err(MuonError::Expression { line }),
+        };
+
+        while let Some(Token::Call(name)) = self.peek() {
+            self.index += 1;
+            args = vec![Expr::Call(line, name, args)];
+        }
+
+        let Ok([expr]) = <[Expr<'a>; 1]>::try_from(args) else {
+            return Err(MuonError::Expression { line });
+        };
+
+        Ok(expr)
+    }
+}
+
+/// Parse a list of expressions
+fn exprs(line: usize, text: &str) -> Result<Vec<Expr<'_>>, MuonError> {
+    let mut parser = Parser {
+        line,
+        tokens: tokens(line, text)?,
+        index: 0,
+    };
+    let exprs = parser.sequence()?;
+
+    // Unbalanced `)`
+    if parser.peek().is_some() {
+        return Err(MuonError::Expression { line });
+    }
+
+    Ok(exprs)
+}
+
+/// Parse one expression
+fn expr(line: usize, text: &str) -> Result<Expr<'_>, MuonError> {
+    let Ok([expr]) = <[Expr<'_>; 1]>::try_from(exprs(line, text)?) else {
+        return Err(MuonError::Expression { line });
+    };
+
+    Ok(expr)
+}
+
+/// Fields of a record, taken out one key at a time
+struct Record<'a> {
+    line: usize,
+    fields: Vec<Item<'a>>,
+}
+
+impl<'a> Record<'a> {
+    /// Create a record from an item, with the value of the item (if any)
+    /// setting the first field
+    fn new(item: Item<'a>, first: &'a str, value: &'a str) -> Self {
+        let mut fields = item.items;
+
+        if !value.is_empty() {
+            fields.insert(
+                0,
+                Item {
+                    line: item.line,
+                    key: first,
+                    value,
+                    items: Vec::new(),
+                },
+            );
+        }
+
+        Self {
+            line: item.line,
+            fields,
+        }
+    }
+
+    /// Take all of the fields with a key
+    fn take(&mut self, key: &str) -> Vec<Item<'a>> {
+        let (taken, rest) = core::mem::take(&mut self.fields)
+            .into_iter()
+            .partition(|field| field.key == key);
+
+        self.fields = rest;
+        taken
+    }
+
+    /// Take a field that can only appear once
+    fn single(&mut self, key: &str) -> Result<Option<Item<'a>>, MuonError> {
+        let mut taken = self.take(key);
+
+        if let Some(repeat) = taken.get(1) {
+            return Err(MuonError::Duplicate { line: repeat.line });
+        }
+
+        Ok(taken.pop())
+    }
+
+    /// Take a field without any fields of its own
+    fn value(&mut self, key: &str) -> Result<Option<Item<'a>>, MuonError> {
+        let field = self.single(key)?;
+
+        if let Some(nested) = field.iter().flat_map(|f| &f.items).next() {
+            return Err(MuonError::Key { line: nested.line });
+        }
+
+        Ok(field)
+    }
+
+    /// Take an optional sampler expression field
+    fn text(&mut self, key: &str) -> Result<Option<Expr<'a>>, MuonError> {
+        self.value(key)?
+            .map(|field| expr(field.line, field.value))
+            .transpose()
+    }
+
+    /// Take a required sampler expression field
+    fn required(&mut self, key: &'static str) -> Result<Expr<'a>, MuonError> {
+        let line = self.line;
+
+        self.text(key)?
+            .ok_or(MuonError::Missing { line, field: key })
+    }
+
+    /// Take an optional integer field
+    fn int(&mut self, key: &str) -> Result<Option<u32>, MuonError> {
+        self.value(key)?
+            .map(|field| {
+                let line = field.line;
+
+                field.value.parse().map_err(|_| MuonError::Number { line })
+            })
+            .transpose()
+    }
+
+    /// Make sure there are no fields left over
+    fn finish(self) -> Result<(), MuonError> {
+        match self.fields.first() {
+            Some(field) => Err(MuonError::Key { line: field.line }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Component of an amplitude envelope
+#[derive(Debug)]
+struct EnvelopeComponent<'a> {
+    /// Seconds to rise/fall or hold
+    time: Expr<'a>,
+    /// Gain at the end of the component
+    gain: Expr<'a>,
+}
+
+/// Sampler function
+#[derive(Debug)]
+enum Function<'a> {
+    Zoid {
+        hz: Expr<'a>,
+        rise: Expr<'a>,
+        hold: Expr<'a>,
+        fall: Expr<'a>,
+    },
+    Sine {
+        hz: Expr<'a>,
+        duty: Option<Expr<'a>>,
+        zero: Option<Expr<'a>>,
+        peak: Option<Expr<'a>>,
+    },
+    White {
+        seed: u32,
+    },
+    Pink {
+        seed: u32,
+    },
+    Phase {
+        func: Expr<'a>,
+        offset: Expr<'a>,
+    },
+    Line(f32),
+    Mix {
+        funcs: Vec<Expr<'a>>,
+    },
+    Gain {
+        func: Expr<'a>,
+        amt: Expr<'a>,
+    },
+    Limit {
+        func: Expr<'a>,
+        ceil: Option<Expr<'a>>,
+        ratio: Option<Expr<'a>>,
+        knee: Option<Expr<'a>>,
+    },
+    Clamp {
+        func: Expr<'a>,
+        min: Option<Expr<'a>>,
+        max: Option<Expr<'a>>,
+        shift: Option<Expr<'a>>,
+    },
+    Envelope {
+        func: Expr<'a>,
+        with: Vec<EnvelopeComponent<'a>>,
+    },
+}
+
+impl<'a> Function<'a> {
+    /// Parse a `func` item
+    fn new(item: Item<'a>) -> Result<Self, MuonError> {
+        let line = item.line;
+        let (name, value) = item
+            .value
+            .split_once(char::is_whitespace)
+            .map(|(name, value)| (name, value.trim()))
+            .unwrap_or((item.value, ""));
+        // Not a record: an optional number
+        if name == "Line" {
+            if let Some(nested) = item.items.first() {
+                return Err(MuonError::Key { line: nested.line });
+            }
+
+            return match value {
+                "" => Ok(Self::Line(0.0)),
+                value => value
+                    .parse()
+                    .map(Self::Line)
+                    .map_err(|_| MuonError::Number { line }),
+            };
+        }
+
+        let first = match name {
+            "Zoid" | "Sine" => "hz",
+            "White" | "Pink" => "seed",
+            "Mix" => "funcs",
+            _ => "func",
+        };
+        let mut record = Record::new(item, first, value);
+        let function = match name {
+            "Zoid" => Self::Zoid {
+                hz: record.required("hz")?,
+                rise: record.required("rise")?,
+                hold: record.required("hold")?,
+                fall: record.required("fall")?,
+            },
+            "Sine" => Self::Sine {
+                hz: record.required("hz")?,
+                duty: record.text("duty")?,
+                zero: record.text("zero")?,
+                peak: record.text("peak")?,
+            },
+            "White" => Self::White {
+                seed: record.int("seed")?.unwrap_or_default(),
+            },
+            "Pink" => Self::Pink {
+                seed: record.int("seed")?.unwrap_or_default(),
+            },
+            "Phase" => Self::Phase {
+                func: record.required("func")?,
+                offset: record.required("offset")?,
+            },
+            "Mix" => {
+                let mut funcs = Vec::new();
+
+                for field in record.take("funcs") {
+                    if let Some(nested) = field.items.first() {
+                        return Err(MuonError::Key { line: nested.line });
+                    }
+                    funcs.extend(exprs(field.line, field.value)?);
+                }
+
+                Self::Mix { funcs }
+            }
+            "Gain" => Self::Gain {
+                func: record.required("func")?,
+                amt: record.required("amt")?,
+            },
+            "Limit" => Self::Limit {
+                func: record.required("func")?,
+                ceil: record.text("ceil")?,
+                ratio: record.text("ratio")?,
+                knee: record.text("knee")?,
+            },
+            "Clamp" => Self::Clamp {
+                func: record.required("func")?,
+                min: record.text("min")?,
+                max: record.text("max")?,
+                shift: record.text("shift")?,
+            },
+            "Envelope" => {
+                let func = record.required("func")?;
+                let mut with = Vec::new();
+
+                for field in record.take("with") {
+                    let value = field.value;
+                    let mut component = Record::new(field, "time", value);
+
+                    with.push(EnvelopeComponent {
+                        time: component.required("time")?,
+                        gain: component.required("gain")?,
+                    });
+                    component.finish()?;
+                }
+
+                Self::Envelope { func, with }
+            }
+            "Reverb" | "Shape" | "Table" => {
+                return Err(MuonError::Unsupported { line })
+            }
+            _ => return Err(MuonError::Function { line }),
+        };
+
+        record.finish()?;
+        Ok(function)
+    }
+
+    /// Get all of the sampler expressions the function uses
+    fn exprs(&self) -> Vec<&Expr<'a>> {
+        match self {
+            Self::Zoid {
+                hz,
+                rise,
+                hold,
+                fall,
+            } => vec![hz, rise, hold, fall],
+            Self::Sine {
+                hz,
+                duty,
+                zero,
+                peak,
+            } => [Some(hz), duty.as_ref(), zero.as_ref(), peak.as_ref()]
+                .into_iter()
+                .flatten()
+                .collect(),
+            Self::White { .. } | Self::Pink { .. } | Self::Line(_) => vec![],
+            Self::Phase { func, offset } => vec![func, offset],
+            Self::Mix { funcs } => funcs.iter().collect(),
+            Self::Gain { func, amt } => vec![func, amt],
+            Self::Limit {
+                func,
+                ceil: a,
+                ratio: b,
+                knee: c,
+            }
+            | Self::Clamp {
+                func,
+                min: a,
+                max: b,
+                shift: c,
+            } => [Some(func), a.as_ref(), b.as_ref(), c.as_ref()]
+                .into_iter()
+                .flatten()
+                .collect(),
+            Self::Envelope { func, with } => core::iter::once(func)
+                .chain(with.iter().flat_map(|c| [&c.time, &c.gain]))
+                .collect(),
+        }
+    }
+}
+
+/// A sampler (function with args)
+#[derive(Debug)]
+struct Sampler<'a> {
+    line: usize,
+    name: &'a str,
+    /// Input samplers
+    args: Vec<&'a str>,
+    func: Function<'a>,
+}
+
+impl<'a> Sampler<'a> {
+    /// Parse a `def` item
+    fn new(item: Item<'a>) -> Result<Self, MuonError> {
+        let (line, name) = (item.line, item.value);
+
+        if !is_name(name) {
+            return Err(MuonError::Syntax { line });
+        }
+
+        let mut record = Record::new(item, "", "");
+        let mut args = Vec::new();
+
+        for field in record.take("args") {
+            if let Some(nested) = field.items.first() {
+                return Err(MuonError::Key { line: nested.line });
+            }
+            for arg in field.value.split_whitespace() {
+                if !is_name(arg) {
+                    return Err(MuonError::Syntax { line: field.line });
+                }
+                if args.contains(&arg) {
+                    return Err(MuonError::Duplicate { line: field.line });
+                }
+                args.push(arg);
+            }
+        }
+
+        let func = record.single("func")?.ok_or(MuonError::Missing {
+            line,
+            field: "func",
+        })?;
+
+        record.finish()?;
+
+        Ok(Self {
+            line,
+            name,
+            args,
+            func: Function::new(func)?,
+        })
+    }
+}
+
+/// Depth-first search state of a sampler
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Visit {
+    New,
+    Open,
+    Done,
+}
+
+/// A parsed MuON document
+#[derive(Debug)]
+struct Document<'a> {
+    /// Samplers, by name
+    def: Vec<Sampler<'a>>,
+    /// Which sampler to stream audio from
+    synth: Expr<'a>,
+}
+
+impl<'a> Document<'a> {
+    fn new(items: Vec<Item<'a>>) -> Result<Self, MuonError> {
+        let mut record = Record {
+            line: 0,
+            fields: items,
+        };
+        let synth = record.text("synth")?.ok_or(MuonError::NoSynth)?;
+        let mut def: Vec<Sampler<'a>> = Vec::new();
+
+        for item in record.take("def") {
+            let sampler = Sampler::new(item)?;
+
+            if def.iter().any(|other| other.name == sampler.name) {
+                return Err(MuonError::Duplicate { line: sampler.line });
+            }
+            def.push(sampler);
+        }
+
+        record.finish()?;
+        Ok(Self { def, synth })
+    }
+
+    /// Find a sampler by name
+    fn find(&self, line: usize, name: &str) -> Result<usize, MuonError> {
+        self.def
+            .iter()
+            .position(|sampler| sampler.name == name)
+            .ok_or_else(|| MuonError::Unknown {
+                line,
+                name: name.into(),
+            })
+    }
+
+    /// Check that the names in an expression resolve, collecting the samplers
+    /// it depends on (and the line of each reference)
+    fn resolve(
+        &self,
+        expr: &Expr<'a>,
+        args: &[&str],
+        deps: &mut Vec<(usize, usize)>,
+    ) -> Result<(), MuonError> {
+        let (line, name, arity) = match expr {
+            Expr::Number(..) => return Ok(()),
+            Expr::Name(_, name) if args.contains(name) => return Ok(()),
+            Expr::Name(line, name) => (*line, name, 0),
+            Expr::Call(line, name, params) => {
+                for param in params {
+                    self.resolve(param, args, deps)?;
+                }
+
+                (*line, name, params.len())
+            }
+        };
+        let index = self.find(line, name)?;
+
+        if self.def[index].args.len() != arity {
+            return Err(MuonError::Arguments {
+                line,
+                name: (*name).into(),
+            });
+        }
+
+        deps.push((index, line));
+        Ok(())
+    }
+
+    /// Depth-first search for a cycle from a sampler
+    fn visit(
+        &self,
+        index: usize,
+        deps: &[Vec<(usize, usize)>],
+        visits: &mut [Visit],
+    ) -> Result<(), MuonError> {
+        visits[index] = Visit::Open;
+
+        for &(dep, line) in &deps[index] {
+            match visits[dep] {
+                Visit::New => self.visit(dep, deps, visits)?,
+                Visit::Open => {
+                    return Err(MuonError::Cycle {
+                        line,
+                        name: self.def[dep].name.into(),
+                    })
+                }
+                Visit::Done => {}
+            }
+        }
+
+        visits[index] = Visit::Done;
+        Ok(())
+    }
+
+    /// Check that all names resolve, and that no sampler depends on itself
+    fn check(&self) -> Result<(), MuonError> {
+        let mut deps = Vec::new();
+
+        for sampler in &self.def {
+            let mut sampler_deps = Vec::new();
+
+            for expr in sampler.func.exprs() {
+                self.resolve(expr, &sampler.args, &mut sampler_deps)?;
+            }
+            deps.push(sampler_deps);
+        }
+        self.resolve(&self.synth, &[], &mut Vec::new())?;
+
+        let mut visits = vec![Visit::New; self.def.len()];
+
+        for index in 0..self.def.len() {
+            if visits[index] == Visit::New {
+                self.visit(index, &deps, &mut visits)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Constant sampler
+fn constant(value: f32) -> Any {
+    Any::Const(Const(Ch32::new(value)))
+}
+
+/// Instantiates samplers as synth nodes
+struct Loader<'a, 'b> {
+    document: &'b Document<'a>,
+    builder: SynthBuilder,
+    /// Sampler index and arguments of each instance, and its output
+    instances: Vec<(usize, Vec<Any>, Any)>,
+}
+
+impl<'a> Loader<'a, '_> {
+    /// Add a node, cached in a new chunk
+    fn push(&mut self, node: Node) -> Any {
+        let chunk = Chunk(self.builder.inputs.buffers.len() as u32);
+
+        self.builder.inputs.reserve_chunk(chunk);
+        self.builder.nodes.push((node, Some(chunk)));
+        Any::Chunk(chunk)
+    }
+
+    /// Get the sampler for an expression, with the arguments in scope
+    fn expr(
+        &mut self,
+        expr: &Expr<'a>,
+        scope: &[(&str, Any)],
+    ) -> Result<Any, MuonError> {
+        match expr {
+            Expr::Number(_, value) => Ok(constant(*value)),
+            Expr::Name(line, name) => {
+                match scope.iter().find(|(arg, _)| arg == name) {
+                    Some((_, sampler)) => Ok(*sampler),
+                    None => self.call(*line, name, Vec::new()),
+                }
+            }
+            Expr::Call(line, name, params) => {
+                let mut args = Vec::new();
+
+                for param in params {
+                    args.push(self.expr(param, scope)?);
+                }
+
+                self.call(*line, name, args)
+            }
+        }
+    }
+
+    /// Get the sampler for an optional expression
+    fn optional(
+        &mut self,
+        expr: &Option<Expr<'a>>,
+        scope: &[(&str, Any)],
+        default: f32,
+    ) -> Result<Any, MuonError> {
+        match expr {
+            Some(expr) => self.expr(expr, scope),
+            None => Ok(constant(default)),
+        }
+    }
+
+    /// Instantiate a sampler (once for each set of arguments)
+    fn call(
+        &mut self,
+        line: usize,
+        name: &str,
+        args: Vec<Any>,
+    ) -> Result<Any, MuonError> {
+        let document = self.document;
+        let index = document.find(line, name)?;
+
+        if let Some((_, _, sampler)) = self
+            .instances
+            .iter()
+            .find(|(i, a, _)| *i == index && *a == args)
+        {
+            return Ok(*sampler);
+        }
+
+        let sampler = &document.def[index];
+        let scope: Vec<(&str, Any)> = sampler
+            .args
+            .iter()
+            .copied()
+            .zip(args.iter().copied())
+            .collect();
+        let output = self.function(&sampler.func, &scope)?;
+
+        self.instances.push((index, args, output));
+        Ok(output)
+    }
+
+    /// Add the nodes for a function
+    fn function(
+        &mut self,
+        function: &Function<'a>,
+        scope: &[(&str, Any)],
+    ) -> Result<Any, MuonError> {
+        let node = match function {
+            Function::Zoid {
+                hz,
+                rise,
+                hold,
+                fall,
+            } => Node::Zoid(
+                self.expr(hz, scope)?,
+                self.expr(rise, scope)?,
+                self.expr(hold, scope)?,
+                self.expr(fall, scope)?,
+                constant(0.0),
+            ),
+            Function::Sine {
+                hz,
+                duty,
+                zero,
+                peak,
+            } => Node::Sine(
+                self.expr(hz, scope)?,
+                self.optional(duty, scope, 0.0)?,
+                self.optional(zero, scope, 0.0)?,
+                self.optional(peak, scope, 0.0)?,
+                constant(0.0),
+            ),
+            Function::White { seed } => Node::White(*seed),
+            Function::Pink { seed } => Node::Pink(*seed),
+            Function::Phase { func, offset } => {
+                return self.phase(func, offset, scope)
+            }
+            Function::Line(value) => return Ok(constant(*value)),
+            Function::Mix { funcs } => {
+                let mut samplers = Vec::new();
+
+                for func in funcs {
+                    samplers.push(self.expr(func, scope)?);
+                }
+
+                Node::Mix(samplers)
+            }
+            Function::Gain { func, amt } => {
+                Node::Gain(self.expr(func, scope)?, self.expr(amt, scope)?)
+            }
+            Function::Limit {
+                func,
+                ceil,
+                ratio,
+                knee,
+            } => Node::Limit(
+                self.expr(func, scope)?,
+                self.optional(ceil, scope, 1.0)?,
+                self.optional(ratio, scope, 0.0)?,
+                self.optional(knee, scope, 0.0)?,
+            ),
+            Function::Clamp {
+                func,
+                min,
+                max,
+                shift,
+            } => Node::Clamp(
+                self.expr(func, scope)?,
+                self.optional(min, scope, -1.0)?,
+                self.optional(max, scope, 1.0)?,
+                self.optional(shift, scope, 0.0)?,
+            ),
+            Function::Envelope { func, with } => {
+                let func = self.expr(func, scope)?;
+                let mut components = Vec::new();
+
+                for component in with {
+                    components.push((
+                        self.expr(&component.time, scope)?,
+                        self.expr(&component.gain, scope)?,
+                    ));
+                }
+
+                Node::Envelope(func, components)
+            }
+        };
+
+        Ok(self.push(node))
+    }
+
+    /// Add a copy of an oscillator, with its phase offset
+    fn phase(
+        &mut self,
+        func: &Expr<'a>,
+        offset: &Expr<'a>,
+        scope: &[(&str, Any)],
+    ) -> Result<Any, MuonError> {
+        let oscillator = match self.expr(func, scope)? {
+            Any::Chunk(chunk) => self
+                .builder
+                .nodes
+                .iter()
+                .find(|(_, dest)| *dest == Some(chunk))
+                .map(|(node, _)| node.clone()),
+            _ => None,
+        };
+        let Some(mut oscillator) = oscillator else {
+            return Err(MuonError::Phase { line: func.line() });
+        };
+        let (Node::Sine(.., old) | Node::Zoid(.., old)) = &mut oscillator
+        else {
+            return Err(MuonError::Phase { line: func.line() });
+        };
+        let offset = self.expr(offset, scope)?;
+
+        if *old == constant(0.0) {
+            *old = offset;
+        } else {
+            *old = self.push(Node::Mix(vec![*old, offset]));
+        }
+
+        Ok(self.push(oscillator))
+    }
+}
+
+impl Synth {
+    /// Load a synth from a document in the MuON twang synthesis language
+    /// (described by `schema.muon`), streaming the `synth` sampler.
+    ///
+    /// Numbers can be used anywhere a sampler is expected, as constants.
+    ///
+    /// ```rust
+    /// use twang::file::Synth;
+    ///
+    /// let mut synth = Synth::from_muon(
+    ///     "\
+    /// def: Tone
+    ///   args: hz
+    ///   func: Sine
+    ///     hz: hz
+    /// def: Chord
+    ///   func: Mix
+    ///     funcs: (220).Tone (330).Tone
+    /// def: Quiet
+    ///   func: Gain
+    ///     func: Chord
+    ///     amt: 0.5
+    /// synth: Quiet
+    /// ",
+    /// )
+    /// .unwrap();
+    /// let chunk = synth.synthesize();
+    /// ```
+    pub fn from_muon(text: &str) -> Result<Self, MuonError> {
+        let document = Document::new(items(text)?)?;
+
+        document.check()?;
+
+        let mut loader = Loader {
+            document: &document,
+            builder: SynthBuilder::new(),
+            instances: Vec::new(),
+        };
+        let root = loader.expr(&document.synth, &[])?;
+        let mut builder = loader.builder;
+
+        builder.nodes.push((Node::Mix(vec![root]), None));
+        Ok(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::f64::consts::TAU;
+
+    use fon::chan::Channel;
+
+    use super::*;
+    use crate::{noise::White, tree::testing};
+
+    /// Render 4800 samples at 48 kHz
+    fn render(text: &str) -> Vec<f32> {
+        let mut synth = Synth::from_muon(text).unwrap();
+
+        (0..150)
+            .flat_map(|_| synth.synthesize())
+            .map(Ch32::to_f32)
+            .collect()
+    }
+
+    /// Check that a rendered document is close to a reference
+    fn assert_near(text: &str, f: impl Fn(f64) -> f64) {
+        testing::assert_near(&render(text), f);
+    }
+
+    fn error(text: &str) -> MuonError {
+        Synth::from_muon(text).unwrap_err()
+    }
+
+    #[test]
+    fn arguments() {
+        assert_near(
+            "\
+# A sine wave at any pitch
+def: Tone
+  args: hz
+  func: Sine
+    hz: hz
+
+def: Chord
+  func: Mix
+    funcs: (220).Tone (330).Tone
+synth: Chord
+",
+            |t| libm::cos(TAU * 220.0 * t) + libm::cos(TAU * 330.0 * t),
+        );
+        assert_near(
+            "\
+def: Tone
+  args: hz amt
+  func: Gain
+    func: hz.Sine
+    amt: amt
+def: Sine
+  args: hz
+  func: Sine hz
+synth: (440 0.5).Tone
+",
+            |t| 0.5 * libm::cos(TAU * 440.0 * t),
+        );
+    }
+
+    #[test]
+    fn shared_instances() {
+        let text = "\
+def: Osc
+  func: Sine 440
+def: Twice
+  func: Mix
+    funcs: Osc
+    funcs: Osc
+synth: Twice
+";
+
+        assert_near(text, |t| 2.0 * libm::cos(TAU * 440.0 * t));
+        // Sine, mixer and output
+        assert_eq!(Synth::from_muon(text).unwrap().nodes.len(), 3);
+    }
+
+    #[test]
+    fn phase() {
+        assert_near(
+            "\
+def: Osc
+  func: Sine 440
+def: Shifted
+  func: Phase
+    func: Osc
+    offset: 0.25
+def: Back
+  func: Phase
+    func: Shifted
+    offset: -0.5
+synth: Back
+",
+            |t| libm::cos(TAU * (440.0 * t - 0.25)),
+        );
+        assert_eq!(
+            error("def: Noise\n  func: White\ndef: P\n  func: Phase\n    func: Noise\n    offset: 0.5\nsynth: P\n"),
+            MuonError::Phase { line: 5 },
+        );
+    }
+
+    #[test]
+    fn shaped_sine() {
+        use crate::tree::line::Line;
+
+        let samples = render(
+            "\
+def: Shaped
+  func: Sine 440
+    duty: 0.5
+    zero: Zero
+    peak: -0.5
+def: Zero
+  func: Line 0.25
+synth: Shaped
+",
+        );
+        let wave =
+            Line(440.0)
+                .osc()
+                .shaped_sine(Line(0.5), Line(0.25), Line(-0.5));
+
+        for (a, b) in samples.into_iter().zip(testing::render(wave)) {
+            assert!((a - b).abs() < 0.001, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn zoid() {
+        let samples = render(
+            "\
+def: Square
+  func: Zoid 480
+    rise: 0
+    hold: 0
+    fall: 1
+def: Late
+  func: Phase
+    func: Square
+    offset: 0.5
+synth: Late
+",
+        );
+
+        // 100 samples per cycle, high for the first half (after the offset)
+        for (i, sample) in samples.into_iter().enumerate() {
+            match i % 100 {
+                // Edges
+                0 | 50 => {}
+                j if j < 50 => assert_eq!(sample, 1.0, "sample {i}"),
+                _ => assert_eq!(sample, -1.0, "sample {i}"),
+            }
+        }
+    }
+
+    #[test]
+    fn effects() {
+        let line = |value: f32, func: &str| {
+            let text = alloc::format!(
+                "def: Value\n  func: Line {value}\ndef: Out\n  func: {func}\n    func: Value\nsynth: Out\n"
+            );
+
+            render(&text)[0]
+        };
+
+        assert_eq!(line(0.5, "Gain\n    amt: 0.5"), 0.25);
+        assert_eq!(line(2.0, "Clamp"), 1.0);
+        assert_eq!(line(-2.0, "Clamp\n    min: -0.5"), -0.5);
+        assert_eq!(line(0.5, "Clamp\n    shift: 0.25"), 0.75);
+        assert_eq!(line(2.0, "Limit"), 1.0);
+        assert_eq!(line(-2.0, "Limit\n    ceil: 0.5"), -0.5);
+        assert_eq!(line(2.0, "Limit\n    ratio: 0.5"), 1.5);
+        assert_eq!(line(0.5, "Limit\n    ceil: 0.5\n    knee: 0.5"), 0.5);
+        // Halfway through the knee, the slope is halfway to the ratio
+        assert_eq!(line(1.5, "Limit\n    knee: 1"), 1.375);
+        assert_eq!(line(3.0, "Limit\n    knee: 1"), 1.5);
+    }
+
+    #[test]
+    fn envelope() {
+        let samples = render(
+            "\
+def: Env
+  func: Envelope
+    func: 0.5
+    with: 0.01
+      gain: 1
+    with:
+      time: 0.01
+      gain: 0.5
+synth: Env
+",
+        );
+
+        assert_eq!(samples[0], 0.0);
+        assert!((samples[240] - 0.25).abs() < 0.001);
+        assert!((samples[480] - 0.5).abs() < 0.001);
+        assert!((samples[720] - 0.375).abs() < 0.001);
+        assert!((samples[4799] - 0.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn noise() {
+        let mut white = White::with_seed(7);
+        let samples = render(
+            "\
+:::
+# Schemas are skipped
+synth: text
+:::
+def: Noise
+  func: White
+    seed: 7
+synth: Noise
+",
+        );
+
+        for sample in samples {
+            assert_eq!(sample, white.step().to_f32());
+        }
+    }
+
+    #[test]
+    fn name_errors() {
+        assert_eq!(
+            error("def: A\n  func: Sine\n    hz: B\nsynth: A\n"),
+            MuonError::Unknown {
+                line: 3,
+                name: "B".into()
+            },
+        );
+        assert_eq!(
+            error("def: A\n  args: hz\n  func: Sine hz\nsynth: A\n"),
+            MuonError::Arguments {
+                line: 4,
+                name: "A".into()
+            },
+        );
+        assert_eq!(
+            error("def: A\n  func: Sine\n    hz: (A).Gain\nsynth: 0\n"),
+            MuonError::Unknown {
+                line: 3,
+                name: "Gain".into()
+            },
+        );
+        // Unused samplers are checked too
+        assert_eq!(
+            error("def: A\n  func: Gain\n    func: A\n    amt: 1\nsynth: 0\n"),
+            MuonError::Cycle {
+                line: 3,
+                name: "A".into()
+            },
+        );
+        assert_eq!(
+            error(
+                "\
+def: A
+  args: x
+  func: Mix
+    funcs: x (x).B
+def: B
+  args: x
+  func: Gain
+    func: x
+    amt: (x).A
+synth: (1).A
+"
+            ),
+            MuonError::Cycle {
+                line: 9,
+                name: "A".into()
+            },
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(
+            error("synth: 0\n   def: A\n"),
+            MuonError::Indent { line: 2 }
+        );
+        assert_eq!(error("  synth: 0\n"), MuonError::Indent { line: 1 });
+        assert_eq!(error("synth 0\n"), MuonError::Syntax { line: 1 });
+        assert_eq!(
+            error("synth: 0\nsynth: 1\n"),
+            MuonError::Duplicate { line: 2 }
+        );
+        assert_eq!(error("def: A\n  func: Line\n"), MuonError::NoSynth);
+        assert_eq!(
+            error("def: A\n  func: Gain\n    func: 1\nsynth: A\n"),
+            MuonError::Missing {
+                line: 2,
+                field: "amt"
+            },
+        );
+        assert_eq!(
+            error("def: A\n  func: Saw 1\nsynth: A\n"),
+            MuonError::Function { line: 2 },
+        );
+        assert_eq!(
+            error("def: A\n  func: Sine 1\ndef: B\n  func: Reverb\nsynth: A\n"),
+            MuonError::Unsupported { line: 4 },
+        );
+        assert_eq!(
+            error("def: A\n  func: Sine 1\n    bpm: 1\nsynth: A\n"),
+            MuonError::Key { line: 3 },
+        );
+        assert_eq!(error("synth: (1 2)\n"), MuonError::Expression { line: 1 });
+        assert_eq!(error("synth: (1\n"), MuonError::Expression { line: 1 });
+        assert_eq!(error("synth: 1)\n"), MuonError::Expression { line: 1 });
+        assert_eq!(error("synth: 1x\n"), MuonError::Number { line: 1 });
+    }
+}
@@ -57,7 +57,7 @@
 //!         }
 //!     }
 //!     // Build synthesis algorithm
-//!     let mut synth = Synth::new(proc, |proc, mut frame: Frame<_, 2>| {
+//!     let mut synth = Synth::new(proc, |proc, mut frame: Frame<_, 2>, _| {
 //!         for (s, pitch) in proc.piano.iter_mut().zip(PITCHES.iter()) {
 //!             for ((i, o), v) in s.iter_mut().enumerate().zip(HARMONICS.iter()) {
 //!                 // Get next sample from oscillator.
@@ -10,6 +10,7 @@ pub(crate) trait Libm: Rem<Output = Self> + Sized {
     fn copysign(self, other: Self) -> Self;
     fn signum(self) -> Self;
     fn exp(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
 }
 
 impl Libm for f32 {
@@ -41,6 +42,33 @@ impl Libm for f32 {
     fn exp(self) -> Self {
         libm::expf(self)
     }
+
+    #[inline(always)]
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let r = self % rhs;
+
+        if r < 0.0 {
+            r + rhs.abs()
+        } else {
+            r
+        }
+    }
+}
+
+/// Check that a sample rate (in Hz) is non-zero, and return it.
+///
+/// Everything that takes a sample rate goes through this (or [`period()`]), so
+/// they all panic on 0 rather than producing NaN or empty delay lines.
+#[inline(always)]
+pub(crate) fn sample_rate(hz: u32) -> u32 {
+    assert_ne!(hz, 0, "Sample rate must be non-zero");
+    hz
+}
+
+/// Seconds per sample at `hz` Hz.
+#[inline(always)]
+pub(crate) fn period(hz: u32) -> f32 {
+    (sample_rate(hz) as f32).recip()
 }
 
 #[cfg(test)]
@@ -6,6 +6,9 @@
 //! also designed to be append-only when writing out to a file.  The final
 //! instruction represents the root node of the synthesis tree.
 //!
+//! Twang files don't embed audio, but can process live or recorded audio fed
+//! into the [`Synth`] with the INP opcode (see [`Wave::input()`]).
+//!
 //! The layout of an instruction is as follows:
 //!
 //! | Byte 1 | Byte 2 | Byte 3 | Byte 4 |
@@ -24,7 +27,11 @@
 //!  - 2: -0 Signal
 //!  - 3: -1 Signal
 //!
-//! All references must be divisible by 4 so that they are aligned.
+//! All references must be divisible by 4 so that they are aligned.  Each
+//! index is relative to the 32-bit word it's stored in, and must point to the
+//! first word of an earlier instruction (or to data, for SIG and VAR).
+//!
+//! Data words must directly precede the first instruction that reads them.
 //!
 //! ## Opcodes
 //!
@@ -48,6 +55,9 @@
 //! ### 3 - PHO
 //!  - `index` points to input node to set phase offset before using oscillator.
 //!
+//! Must be directly followed by the oscillator instruction (SIN, RMP, BEZ, SQR
+//! or PUL) to offset the phase of.
+//!
 //! ### 4 - RMP
 //!  - `index_a` points to input node to determine hz.
 //!
@@ -79,7 +89,9 @@
 //! |--------|--------|--------|--------|
 //! | Input1 | Input2 | Input3 | Clamp  |
 //!
-//!  - `input` is a unique identifier fo the input.  Range is -1 to 1.
+//!  - `input` is a unique identifier fo the input, used as the index into the
+//!    parameters passed to [`Synth::stream()`].  Must be less than 4096.
+//!    Range is -1 to 1.
 //!  - `clamp` is a boolean indicating whether or not the user input should be
 //!    clamped to be in range.
 //!
@@ -103,95 +115,66 @@
 //!  - `index` points to input node for random key.
 //!
 //! ### 16 - MIN
-//!  - `index` points to input node.
+//!  - `index_a` points to input node of primary wave.
+//!  - `index_b` points to input node of secondary wave.
+//!  - `opcode_b` can be 0 to end instruction, or 1 to compare more waves.
 //!
 //! ### 17 - MAX
-//!  - `index` points to input node.
+//!  - `index_a` points to input node of primary wave.
+//!  - `index_b` points to input node of secondary wave.
+//!  - `opcode_b` can be 0 to end instruction, or 1 to compare more waves.
+//!
+//! ### 18 - NEA
+//!  - `index_a` points to input node to limit the amplitude of.
+//!  - `index_b` points to input node of the maximum amplitude (sign ignored).
+//!  - `opcode_b` must be 0 / FALSE.
+//!
+//! ### 19 - FAR
+//!  - `index_a` points to input node to limit the amplitude of.
+//!  - `index_b` points to input node of the minimum amplitude (sign ignored).
+//!  - `opcode_b` must be 0 / FALSE.
+//!
+//! ### 20 - INP
+//!  - `index` is reserved, and must be 0.
+//!
+//! Plays audio fed into the synth with [`Synth::feed()`].
 
 #![allow(warnings)]
 
-use fon::Sink;
+mod error;
+mod program;
+mod save;
 
-use alloc::vec::Vec;
+use core::marker::PhantomData;
 
-/// A synthesis instruction
-#[repr(u8)]
-enum Inst {
-    /// Constant signal
-    Sig = 0,
-    /// Mix (add) audio together
-    Mix = 1,
-    /// Sine wave (default)
-    Sin = 2,
-    /// Phase offset modifier
-    Pho = 3,
-    /// Ramp wave
-    Rmp = 4,
-    /// Ramp wave with bezier curve
-    Bez = 5,
-    /// Square wave
-    Sqr = 6,
-    /// Pulse wave
-    Pul = 7,
-    /// Store audio buffer
-    Let = 8,
-    /// Clear audio buffer - audio buffers should be cleared early, and none
-    /// should be left at the root node.
-    Del = 9,
-    /// Define user input node
-    Var = 10,
-    /// Clip by clamping audio from -1 to 1
-    Clp = 11,
-    /// Multiply waves treating -1 as ground
-    Mul = 12,
-    /// Multiply waves treating 0 as ground
-    Amp = 13,
-    /// White noise
-    Wht = 14,
-    /// Pink noise
-    Pnk = 15,
-    /// Minimum amplitude
-    Min = 16,
-    /// Maximum amplitude
-    Max = 17,
-}
+use fon::{
+    chan::{Ch32, Channel},
+    pos::Mono,
+    Audio, Sink,
+};
 
-/// A synthesis file
-#[derive(Debug, Copy, Clone)]
-enum File<'a> {
-    /// Constant signal
-    Sig(f32),
-    /// Mix (add) audio together
-    Mix(&'a [u8]),
-    /// Sine wave
-    Sine {
-        /// Frequency of the waveform
-        hz: &'a [u8],
-    },
-    /// Ramp wave
-    Ramp {
-        /// Frequency of the waveform
-        hz: &'a [u8],
-        /// Amount of bezier curving to apply; set to 0 for sawtooth wave
-        curve: &'a [u8],
-    },
-    /// Pulse wave
-    Pulse {
-        /// Frequency of the waveform
-        hz: &'a [u8],
-        /// Duty cycle; set to 0 for square wave
-        duty: &'a [u8],
-        /// Alias; set to -1 for no aliasing (pure pulse wave), set to 0 for
-        /// trapazoid wave, and 1 for triangle wave
-        alias: &'a [u8],
-    },
-}
+use crate::noise::{Generator, Pink, White};
+
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use self::program::MAX_INPUTS;
+pub use self::{
+    error::TwangFileError,
+    program::{Op, Program},
+};
 
 /// A synthesis node
 #[derive(Debug, Copy, Clone)]
 enum Node<'a> {
     /// Constant signal
     Sig(f32),
+    /// User input
+    Var {
+        /// Index of the user parameter
+        input: u32,
+        /// Whether or not to clamp the parameter from -1 to 1
+        clamp: bool,
+    },
     /// Mix (add) audio together
     Mix(&'a [Node<'a>]),
     /// Sine wave
@@ -213,10 +196,40 @@ enum Node<'a> {
         /// Duty cycle; set to 0 for square wave
         duty: &'a Node<'a>,
         /// Alias; set to -1 for no aliasing (pure pulse wave), set to 0 for
-        /// trapazoid wave, and 1 for triangle wave.
+        /// trapazoid wave, and 1 for triangle wave.  This is the slope of the
+        /// edges, not anti-aliasing (see [`BlPulse`](crate::osc::BlPulse)).
         alias: &'a Node<'a>,
     },
 
+    /// Phase offset
+    Phase {
+        /// Oscillator to offset the phase of
+        osc: &'a Node<'a>,
+        /// Offset in cycles
+        offset: &'a Node<'a>,
+    },
+    /// White noise
+    White {
+        /// Random key to seed the noise
+        key: &'a Node<'a>,
+    },
+    /// Pink noise
+    Pink {
+        /// Random key to seed the noise
+        key: &'a Node<'a>,
+    },
+
+    /// Clip by clamping from -1 to 1
+    Clip(&'a Node<'a>),
+    /// Minimum value
+    Min(&'a [Node<'a>]),
+    /// Maximum value
+    Max(&'a [Node<'a>]),
+    /// Limit amplitude to be no farther from zero than another wave
+    Near(&'a Node<'a>, &'a Node<'a>),
+    /// Limit amplitude to be no nearer to zero than another wave
+    Far(&'a Node<'a>, &'a Node<'a>),
+
     /// Multiply audio nodes together
     Mul(&'a [Node<'a>]),
     /// Ground zero multiply
@@ -224,36 +237,114 @@ enum Node<'a> {
 
     /// Source from Twang file data
     File(&'a [u8]),
+    /// Audio fed into the synth
+    Input,
 }
 
 impl Node<'_> {
-    /// Recursively count the number of oscillators
-    fn count_osc(&self) -> usize {
+    /// Recursively append instructions to a program, returning the root index
+    fn compile(&self, program: &mut Program) -> Result<usize, TwangFileError> {
         use Node::*;
-        match *self {
-            Sig(_) => 0,
+        Ok(match *self {
+            Sig(v) => match [0.0f32, 1.0, -0.0, -1.0]
+                .iter()
+                .position(|x| x.to_bits() == v.to_bits())
+            {
+                Some(special) => special,
+                None => program.push(Op::Sig(v)),
+            },
+            Var { input, clamp } => program.push(Op::Var { input, clamp }),
             Mix(nodes) => {
-                let mut count = 0;
-                for node in nodes {
-                    count += node.count_osc();
-                }
-                count
+                let nodes = nodes
+                    .iter()
+                    .map(|x| x.compile(program))
+                    .collect::<Result<_, _>>()?;
+
+                program.push(Op::Mix(nodes))
+            }
+            Sine { hz } => {
+                let hz = hz.compile(program)?;
+
+                program.push(Op::Sine { hz })
+            }
+            Ramp { hz, curve } => {
+                let hz = hz.compile(program)?;
+                let curve = curve.compile(program)?;
+
+                program.push(Op::Ramp { hz, curve })
             }
-            Sine { hz } => 1 + hz.count_osc(),
-            Ramp { hz, curve } => 1 + hz.count_osc() + curve.count_osc(),
             Pulse { hz, duty, alias } => {
-                1 + hz.count_osc() + duty.count_osc() + alias.count_osc()
+                let hz = hz.compile(program)?;
+                let duty = duty.compile(program)?;
+                let alias = alias.compile(program)?;
+
+                program.push(Op::Pulse { hz, duty, alias })
+            }
+            Phase { osc, offset } => {
+                let offset = offset.compile(program)?;
+                let osc = osc.compile(program)?;
+
+                program.push(Op::Phase { osc, offset })
+            }
+            White { key } => {
+                let key = key.compile(program)?;
+
+                program.push(Op::White { key })
+            }
+            Pink { key } => {
+                let key = key.compile(program)?;
+
+                program.push(Op::Pink { key })
+            }
+            Clip(node) => {
+                let node = node.compile(program)?;
+
+                program.push(Op::Clip(node))
+            }
+            Min(nodes) => {
+                let nodes = nodes
+                    .iter()
+                    .map(|x| x.compile(program))
+                    .collect::<Result<_, _>>()?;
+
+                program.push(Op::Min(nodes))
+            }
+            Max(nodes) => {
+                let nodes = nodes
+                    .iter()
+                    .map(|x| x.compile(program))
+                    .collect::<Result<_, _>>()?;
+
+                program.push(Op::Max(nodes))
+            }
+            Near(input, limit) => {
+                let input = input.compile(program)?;
+                let limit = limit.compile(program)?;
+
+                program.push(Op::Near { input, limit })
+            }
+            Far(input, limit) => {
+                let input = input.compile(program)?;
+                let limit = limit.compile(program)?;
+
+                program.push(Op::Far { input, limit })
             }
             Mul(nodes) => {
-                let mut count = 0;
-                for node in nodes {
-                    count += node.count_osc();
-                }
-                count
+                let nodes = nodes
+                    .iter()
+                    .map(|x| x.compile(program))
+                    .collect::<Result<_, _>>()?;
+
+                program.push(Op::Mul(nodes))
             }
-            Amp(a, b) => a.count_osc() + b.count_osc(),
-            File(_) => todo!(),
-        }
+            Amp(a, b) => {
+                let nodes = vec![a.compile(program)?, b.compile(program)?];
+
+                program.push(Op::Amp(nodes))
+            }
+            File(bytes) => program.splice(&Program::parse(bytes)?),
+            Input => program.push(Op::Input),
+        })
     }
 }
 
@@ -283,10 +374,75 @@ impl Wave<'static> {
     pub const fn sig(value: f32) -> Self {
         Self(Node::Sig(value))
     }
+
+    /// User parameter, clamped from -1 to 1
+    ///
+    /// `input` is the index into the parameters passed to [`Synth::stream()`].
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Volume control for a 220.0 hertz sine wave
+    /// const WAVE: Wave = Wave::sig(220.0).sine().amp(&Wave::var(0));
+    /// ```
+    ///
+    /// # Panics
+    /// If `input` is 4096 or more.
+    pub const fn var(input: u32) -> Self {
+        assert!(input < MAX_INPUTS, "Input identifier out of range");
+
+        Self(Node::Var { input, clamp: true })
+    }
+
+    /// User parameter, without clamping
+    ///
+    /// `input` is the index into the parameters passed to [`Synth::stream()`].
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Sine wave with frequency controlled by the user
+    /// const WAVE: Wave = Wave::var_unclamped(0).sine();
+    /// ```
+    ///
+    /// # Panics
+    /// If `input` is 4096 or more.
+    pub const fn var_unclamped(input: u32) -> Self {
+        assert!(input < MAX_INPUTS, "Input identifier out of range");
+
+        Self(Node::Var {
+            input,
+            clamp: false,
+        })
+    }
+
+    /// Audio fed into the synth with [`Synth::feed()`], for processing live
+    /// or recorded audio (silence when the fed audio runs out)
+    ///
+    /// Every input in a waveform plays the same audio.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Fed audio, clipped at half amplitude
+    /// const WAVE: Wave = Wave::input().near(&Wave::sig(0.5));
+    /// ```
+    pub const fn input() -> Self {
+        Self(Node::Input)
+    }
 }
 
 impl<'a> Wave<'a> {
     /// Load Twang file
+    ///
+    /// The file is decoded when the [`Synth`] is created, and can be used
+    /// anywhere a const-built wave can.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Constant 440.0 hertz signal
+    /// const HZ: &[u8] = b"\xFF\xFETwAnG\0\0\0\xDC\x43\x04\0\0\0";
+    ///
+    /// // Sine wave at 440.0 hertz
+    /// const WAVE: Wave = Wave::file(HZ).sine();
+    /// ```
     pub const fn file(bytes: &'a [u8]) -> Self {
         Self(Node::File(bytes))
     }
@@ -386,6 +542,64 @@ impl<'a> Wave<'a> {
         })
     }
 
+    /// Offset the phase of an oscillator (phase modulation)
+    ///
+    /// The offset is in cycles, so an offset of 0.5 shifts the oscillator by
+    /// half a period.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Sine wave at 440.0 hertz, phase modulated by a sine wave at 660.0 hz
+    /// const WAVE: Wave = Wave::sig(440.0)
+    ///     .sine()
+    ///     .phase(&Wave::sig(660.0).sine());
+    /// ```
+    ///
+    /// # Panics
+    /// If `self` isn't a sine, ramp or pulse oscillator.
+    pub const fn phase(&'a self, offset: &'a Self) -> Self {
+        assert!(
+            matches!(
+                self.0,
+                Node::Sine { .. } | Node::Ramp { .. } | Node::Pulse { .. }
+            ),
+            "Phase offset must be applied to an oscillator",
+        );
+
+        Self(Node::Phase {
+            osc: &self.0,
+            offset: &offset.0,
+        })
+    }
+
+    /// White noise, seeded from the random key
+    ///
+    /// The noise sequence restarts whenever the key changes, so renders can be
+    /// reproduced by using the same key.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // White noise
+    /// const WAVE: Wave = Wave::ZERO.white();
+    /// ```
+    pub const fn white(&'a self) -> Self {
+        Self(Node::White { key: &self.0 })
+    }
+
+    /// Pink noise, seeded from the random key
+    ///
+    /// The noise sequence restarts whenever the key changes, so renders can be
+    /// reproduced by using the same key.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Pink noise
+    /// const WAVE: Wave = Wave::ZERO.pink();
+    /// ```
+    pub const fn pink(&'a self) -> Self {
+        Self(Node::Pink { key: &self.0 })
+    }
+
     /// Amplify an audio wave
     ///
     /// ## Warning
@@ -403,6 +617,19 @@ impl<'a> Wave<'a> {
     }
 
     /// Multiply audio waves together, treating -1 as ground / zero.
+    ///
+    /// Each wave is shifted into the range 0 to 1 before multiplying, and the
+    /// product is shifted back, so the output is -1 whenever any input is -1,
+    /// and +1 is the identity.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Sine wave at 440.0 hertz, gated by a square wave at 2.0 hertz
+    /// const WAVE: Wave = Wave::mul(&[
+    ///     Wave::sig(440.0).sine(),
+    ///     Wave::sig(2.0).sq(),
+    /// ]);
+    /// ```
     pub const fn mul(nodes: &'a [Self]) -> Self {
         Self(Node::Mul(Self::as_nodes(nodes)))
     }
@@ -412,6 +639,113 @@ impl<'a> Wave<'a> {
         Self(Node::Mix(Self::as_nodes(nodes)))
     }
 
+    /// Clip audio wave by clamping from -1 to 1
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Overdriven sine wave at 220.0 hertz
+    /// const WAVE: Wave = Wave::sig(220.0).sine().amp(&Wave::sig(4.0)).clip();
+    /// ```
+    pub const fn clip(&'a self) -> Self {
+        Self(Node::Clip(&self.0))
+    }
+
+    /// Minimum value of audio waves (-0.5 is less than -0.25; for minimum
+    /// amplitude use [`Wave::near()`])
+    ///
+    /// An empty list produces silence.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Sine wave at 220.0 hertz, with the top half flattened
+    /// const WAVE: Wave = Wave::min(&[Wave::sig(220.0).sine(), Wave::sig(0.5)]);
+    /// ```
+    pub const fn min(nodes: &'a [Self]) -> Self {
+        Self(Node::Min(Self::as_nodes(nodes)))
+    }
+
+    /// Maximum value of audio waves (-0.25 is greater than -0.5; for maximum
+    /// amplitude use [`Wave::far()`])
+    ///
+    /// An empty list produces silence.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Half-wave rectified sine wave at 220.0 hertz
+    /// const WAVE: Wave = Wave::max(&[Wave::sig(220.0).sine(), Wave::sig(0.0)]);
+    /// ```
+    pub const fn max(nodes: &'a [Self]) -> Self {
+        Self(Node::Max(Self::as_nodes(nodes)))
+    }
+
+    /// Limit amplitude of audio wave to be no farther from zero than `limit`
+    ///
+    /// The sign of `limit` is ignored.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Sine wave at 220.0 hertz, symmetrically clipped at half amplitude
+    /// const WAVE: Wave = Wave::sig(220.0).sine().near(&Wave::sig(0.5));
+    /// ```
+    pub const fn near(&'a self, limit: &'a Self) -> Self {
+        Self(Node::Near(&self.0, &limit.0))
+    }
+
+    /// Limit amplitude of audio wave to be no nearer to zero than `limit`
+    ///
+    /// The sign of `limit` is ignored, and zero is treated as positive.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Sine wave at 220.0 hertz, with a gap around the zero crossings
+    /// const WAVE: Wave = Wave::sig(220.0).sine().far(&Wave::sig(0.5));
+    /// ```
+    pub const fn far(&'a self, limit: &'a Self) -> Self {
+        Self(Node::Far(&self.0, &limit.0))
+    }
+
+    /// Encode as a twang file.
+    ///
+    /// ```rust
+    /// # use twang::next::Wave;
+    /// // Sine wave at 440.0 hertz
+    /// const WAVE: Wave = Wave::sig(440.0).sine();
+    ///
+    /// let bytes = WAVE.to_bytes();
+    ///
+    /// assert_eq!(&bytes[..8], b"\xFF\xFETwAnG\0");
+    /// ```
+    ///
+    /// # Panics
+    /// If a loaded twang file is malformed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.program().expect("Malformed twang file").to_bytes()
+    }
+
+    /// Append twang file to a `Vec`.
+    ///
+    /// Identical subtrees are only written once, and then shared with the LET
+    /// and DEL cache instructions.
+    ///
+    /// # Panics
+    /// If a loaded twang file is malformed.
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        self.program().expect("Malformed twang file").write_to(out)
+    }
+
+    /// Build the instruction graph for this waveform
+    fn program(&self) -> Result<Program, TwangFileError> {
+        let mut program = Program::default();
+        let root = self.0.compile(&mut program)?;
+
+        // Make sure the root node is the final instruction
+        if root != program.root() {
+            program.push(Op::Let(root));
+        }
+
+        Ok(program)
+    }
+
     /// Get node slice from wave slice
     // Safe transmute because of `repr(transparent)`
     #[allow(unsafe_code)]
@@ -423,52 +757,153 @@ impl<'a> Wave<'a> {
 /// A synthesizer.
 #[derive(Debug)]
 pub struct Synth<'a> {
-    wave: Option<Wave<'a>>,
-
-    // /// Chunks are made of 32 samples (one for each LET instruction)
-    // chunks: Vec<[f32; 32]>,
-    /// Stack of buffers
-    stack: Vec<[f32; 32]>,
-    /// Store phases (one for each oscillator)
+    /// Synthesis instructions
+    program: Program,
+    /// Output buffers (one for each instruction)
+    buffers: Vec<[f32; 32]>,
+    /// Store phases (one for each instruction)
     phase: Vec<f32>,
-    /// How many samples have been read from the stack
+    /// How many samples have been read from the root buffer
     index: usize,
+    /// White noise generators and their keys (one for each instruction)
+    white: Vec<Option<(u32, White)>>,
+    /// Pink noise generators and their keys (one for each instruction)
+    pink: Vec<Option<(u32, Pink)>>,
+    /// Whether each instruction's output is read (oscillators wrapped by a
+    /// phase offset are only evaluated by the phase offset)
+    live: Vec<bool>,
+    /// User parameters at the start of the current chunk
+    params_old: Vec<f32>,
+    /// User parameters at the end of the current chunk
+    params: Vec<f32>,
+    /// Audio fed into the synth, not yet synthesized
+    input: VecDeque<f32>,
+    /// Fed audio for the current chunk
+    input_chunk: [f32; 32],
+    _wave: PhantomData<Wave<'a>>,
 }
 
 impl<'a> Synth<'a> {
     /// Create a new synthesizer for a parameterized waveform.
+    ///
+    /// # Panics
+    /// If a loaded twang file is malformed.  Use [`Synth::try_new()`] to
+    /// handle untrusted files.
     pub fn new(wave: Wave<'a>) -> Self {
-        let phase = {
-            let mut phase = Vec::new();
-            phase.resize(wave.0.count_osc(), 0.0);
-            phase
-        };
-        let stack = Vec::from([[0.0; 32]]);
+        Self::try_new(wave).expect("Malformed twang file")
+    }
+
+    /// Create a new synthesizer for a parameterized waveform, failing if a
+    /// loaded twang file is malformed.
+    ///
+    /// ```rust
+    /// # use twang::next::{Synth, TwangFileError, Wave};
+    /// const WAVE: Wave = Wave::file(b"not a twang file").sine();
+    ///
+    /// assert_eq!(Synth::try_new(WAVE).unwrap_err(), TwangFileError::Magic);
+    /// ```
+    pub fn try_new(wave: Wave<'a>) -> Result<Self, TwangFileError> {
+        let program = wave.program()?;
+        let buffers = vec![[0.0; 32]; program.ops().len()];
+        let phase = vec![0.0; program.ops().len()];
         let index = 32;
-        let wave = Some(wave);
+        let white = vec![None; program.ops().len()];
+        let pink = vec![None; program.ops().len()];
+        let params = program
+            .ops()
+            .iter()
+            .filter_map(|op| match *op {
+                Op::Var { input, .. } => Some(input as usize + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let params = vec![0.0; params];
+        let mut live = vec![false; program.ops().len()];
 
-        // panic!("{}", phase.len());
+        live[program.root()] = true;
+        for op in program.ops() {
+            match *op {
+                Op::Phase { offset, .. } => live[offset] = true,
+                ref op => op.inputs().into_iter().for_each(|i| live[i] = true),
+            }
+        }
 
-        Synth {
-            wave,
+        Ok(Synth {
+            program,
+            buffers,
             phase,
-            stack,
             index,
-        }
+            white,
+            pink,
+            live,
+            params_old: Vec::new(),
+            params,
+            input: VecDeque::new(),
+            input_chunk: [0.0; 32],
+            _wave: PhantomData,
+        })
     }
 
     /// Return the number of configurable parameters for this waveform.
+    ///
+    /// This is one more than the highest input index used by the waveform.
     pub fn params(&self) -> usize {
-        0
+        self.params.len()
+    }
+
+    /// Feed audio (at the sample rate of the stream) to be played by
+    /// [`Wave::input()`], after any audio fed before.
+    ///
+    /// Audio with more channels is mixed down to mono.
+    ///
+    /// ```rust
+    /// use fon::{chan::Ch32, Audio};
+    /// use twang::next::{Synth, Wave};
+    ///
+    /// const WAVE: Wave = Wave::input().clip();
+    ///
+    /// let mut synth = Synth::new(WAVE);
+    /// let recording = Audio::<Ch32, 1>::with_silence(48_000, 480);
+    /// let mut output = Audio::<Ch32, 1>::with_silence(48_000, 480);
+    ///
+    /// synth.feed(&recording);
+    /// synth.stream(output.sink(), &[]);
+    /// ```
+    pub fn feed<Ch, const CH: usize>(&mut self, audio: &Audio<Ch, CH>)
+    where
+        Ch: Channel,
+        Ch32: From<Ch>,
+    {
+        self.input.extend(
+            audio
+                .iter()
+                .map(|frame| frame.to::<Ch32, 1>()[Mono].to_f32()),
+        );
     }
 
     /// Run synthesis with user parameters, streaming output into the provided
     /// [`Sink`]
+    ///
+    /// Parameter changes are smoothed over each 32-sample chunk to avoid
+    /// clicks.
+    ///
+    /// # Panics
+    /// If the length of `params` doesn't match [`Synth::params()`].
     pub fn stream<Ch, K, const N: usize>(&mut self, mut sink: K, params: &[f32])
     where
         Ch: fon::chan::Channel + From<fon::chan::Ch32>,
         K: Sink<Ch, N>,
     {
+        assert_eq!(params.len(), self.params(), "Wrong number of parameters");
+
+        self.params.copy_from_slice(params);
+
+        // Don't smooth from the default values on the first stream
+        if self.params_old.is_empty() {
+            self.params_old.clone_from(&self.params);
+        }
+
         let sample_rate: u32 = sink.sample_rate().into();
         let synth_iter = SynthIter(self, sample_rate);
 
@@ -477,151 +912,258 @@ impl<'a> Synth<'a> {
 
     /// Synthesis
     fn synthesize(&mut self, sample_rate: u32) -> f32 {
+        let root = self.program.root();
+
         if self.index < 32 {
-            let sample = self.stack[0][self.index];
+            let sample = self.buffers[root][self.index];
             self.index += 1;
             return sample;
         }
 
         let delta = (f64::from(sample_rate)).recip() as f32;
-        let wave = self.wave.take().unwrap();
-        self.node(&wave.0, delta, &mut 0);
-        self.wave = Some(wave);
+        let len = self.input.len().min(32);
+
+        self.input_chunk = [0.0; 32];
+        for (sample, fed) in
+            self.input_chunk.iter_mut().zip(self.input.drain(..len))
+        {
+            *sample = fed;
+        }
+
+        // Instructions only reference earlier instructions, so evaluating in
+        // order always has the inputs ready.
+        for i in 0..self.buffers.len() {
+            if self.live[i] {
+                self.op(i, delta);
+            }
+        }
+
+        self.params_old.copy_from_slice(&self.params);
 
         self.index = 1;
-        self.stack[0][0]
+        self.buffers[root][0]
     }
 
-    fn node(&mut self, node: &Node<'_>, delta: f32, osc: &mut usize) {
-        match node {
-            Node::Sig(v) => {
-                self.stack.last_mut().unwrap().fill(*v);
+    fn op(&mut self, i: usize, delta: f32) {
+        let (inputs, output) = self.buffers.split_at_mut(i);
+        let output = &mut output[0];
+        let phase = &mut self.phase[i];
+
+        match &self.program.ops()[i] {
+            Op::Sig(v) => output.fill(*v),
+            Op::Var { input, clamp } => {
+                let old = self.params_old[*input as usize];
+                let new = self.params[*input as usize];
+
+                for (i, out) in output.iter_mut().enumerate() {
+                    *out = old + (new - old) * (i as f32 / 32.0);
+                    if *clamp {
+                        *out = out.clamp(-1.0, 1.0);
+                    }
+                }
             }
-            Node::Mix(nodes) => {
-                for node in *nodes {
-                    self.stack.push([0.0; 32]);
-                    self.node(node, delta, osc);
-                    let buffer = self.stack.pop().unwrap();
-                    for (out, sample) in
-                        self.stack.last_mut().unwrap().iter_mut().zip(buffer)
-                    {
+            Op::Mix(nodes) => {
+                output.fill(0.0);
+                for node in nodes {
+                    for (out, sample) in output.iter_mut().zip(inputs[*node]) {
                         *out += sample;
                     }
                 }
             }
-            Node::Sine { hz } => {
-                let this = *osc;
-                *osc += 1;
-                self.node(hz, delta, osc);
-                for i in self.stack.last_mut().unwrap() {
-                    let hertz = *i;
-                    *i = libm::cosf(self.phase[this] * core::f32::consts::TAU);
-                    self.phase[this] = (self.phase[this] + delta * hertz) % 1.0;
+            op @ (Op::Sine { .. } | Op::Ramp { .. } | Op::Pulse { .. }) => {
+                for (j, out) in output.iter_mut().enumerate() {
+                    let (sample, hertz) = osc(op, inputs, j, *phase);
+
+                    *out = sample;
+                    *phase = (*phase + delta * hertz) % 1.0;
                 }
             }
-            Node::Ramp { hz, curve } => {
-                self.stack.push([0.0; 32]);
-                self.node(curve, delta, osc);
-                let curve = self.stack.pop().unwrap();
-
-                let this = *osc;
-                *osc += 1;
-                self.node(hz, delta, osc);
-                for (i, curve) in
-                    self.stack.last_mut().unwrap().iter_mut().zip(curve)
-                {
-                    let hertz = *i;
-                    *i = 1.0 - (self.phase[this] * 2.0);
-                    if i.is_sign_negative() {
-                        let v = *i + 1.0;
-                        *i = v - v * curve * (v - 1.0) - 1.0;
-                    } else {
-                        let w = 1.0 - *i;
-                        *i = 1.0 - w + w * curve * (w - 1.0);
+            Op::Phase { osc: op, offset } => {
+                let op = &self.program.ops()[*op];
+
+                for (j, out) in output.iter_mut().enumerate() {
+                    let mut shifted = (*phase + inputs[*offset][j]) % 1.0;
+
+                    if shifted < 0.0 {
+                        shifted += 1.0;
+                    }
+
+                    let (sample, hertz) = osc(op, inputs, j, shifted);
+
+                    *out = sample;
+                    *phase = (*phase + delta * hertz) % 1.0;
+                }
+            }
+            Op::Let(node) | Op::Del(node) => *output = inputs[*node],
+            Op::Input => *output = self.input_chunk,
+            Op::Amp(nodes) => {
+                output.fill(1.0);
+                for node in nodes {
+                    for (out, sample) in output.iter_mut().zip(inputs[*node]) {
+                        *out *= sample;
+                    }
+                }
+            }
+            Op::Mul(nodes) => {
+                // Shift -1..1 to 0..1, so that -1 is ground
+                output.fill(1.0);
+                for node in nodes {
+                    for (out, sample) in output.iter_mut().zip(inputs[*node]) {
+                        *out *= (sample + 1.0) * 0.5;
                     }
-                    self.phase[this] = (self.phase[this] + delta * hertz) % 1.0;
+                }
+                for out in output.iter_mut() {
+                    *out = *out * 2.0 - 1.0;
+                }
+            }
+            Op::White { key } => {
+                let white = &mut self.white[i];
+
+                for (out, key) in output.iter_mut().zip(inputs[*key]) {
+                    *out = noise(white, key);
                 }
             }
-            Node::Pulse { hz, duty, alias } => {
-                self.stack.push([0.0; 32]);
-                self.node(alias, delta, osc);
-                let alias = self.stack.pop().unwrap();
-
-                self.stack.push([0.0; 32]);
-                self.node(duty, delta, osc);
-                let duty = self.stack.pop().unwrap();
-
-                let this = *osc;
-                *osc += 1;
-                self.node(hz, delta, osc);
-                for ((i, alias), duty) in self
-                    .stack
-                    .last_mut()
-                    .unwrap()
-                    .iter_mut()
-                    .zip(alias)
-                    .zip(duty)
+            Op::Pink { key } => {
+                let pink = &mut self.pink[i];
+
+                for (out, key) in output.iter_mut().zip(inputs[*key]) {
+                    *out = noise(pink, key);
+                }
+            }
+            Op::Clip(node) => {
+                for (out, sample) in output.iter_mut().zip(inputs[*node]) {
+                    *out = sample.clamp(-1.0, 1.0);
+                }
+            }
+            Op::Min(nodes) | Op::Max(nodes) => {
+                let Some((first, rest)) = nodes.split_first() else {
+                    output.fill(0.0);
+                    return;
+                };
+                let f = match self.program.ops()[i] {
+                    Op::Min(_) => f32::min,
+                    _ => f32::max,
+                };
+
+                *output = inputs[*first];
+                for node in rest {
+                    for (out, sample) in output.iter_mut().zip(inputs[*node]) {
+                        *out = f(*out, sample);
+                    }
+                }
+            }
+            Op::Near { input, limit } => {
+                for ((out, sample), limit) in
+                    output.iter_mut().zip(inputs[*input]).zip(inputs[*limit])
                 {
-                    let hertz = *i;
-                    let phase = self.phase[this];
-
-                    let sa = (alias * 0.5) + 0.5; // size of alias / phase
-                    let sp = (duty * 0.5) + 0.5; // size of positive (+) / phase
-                    let sn = 1.0 - sp; // size of negative (+) / phase
-                    let dc = sp * 0.5; // center of descent alias / phase
-                    let ac = 1.0 - dc; // center of ascent alias / phase
-                    let lc = dc + (ac - dc) * 0.5; // center of (-1) / phase
-                    let lc = lc + (sa * duty * 0.5); //
-                    let pa = sp * sa; // size of positive alias / phase
-                    let na = sn * sa; // size of negative alias / phase
-                    let db = (sp - pa) * 0.5; // descent begin
-                    let ae = 1.0 - db; // ascent end
-                    let de = lc - (sn - na) * 0.5; // descent end
-                    let ab = lc + (sn - na) * 0.5; // ascent begin
-
-                    *i = if phase < db {
-                        // Before descent begin
-                        1.0
-                    } else if phase < de {
-                        // Before descent end
-                        let sd = de - db; // size of descent
-
-                        1.0 - 2.0 * (phase - db) / sd
-                    } else if phase < ab {
-                        // Before ascent begin
-                        -1.0
-                    } else if phase < ae {
-                        // Before ascent end
-                        let sa = ae - ab; // size of ascent
-
-                        -1.0 + 2.0 * (phase - ab) / sa
+                    let limit = limit.abs();
+
+                    *out = if sample < 0.0 {
+                        sample.max(-limit)
                     } else {
-                        // After ascent end, until center (+) point
-                        1.0
+                        sample.min(limit)
                     };
-
-                    self.phase[this] = (self.phase[this] + delta * hertz) % 1.0;
                 }
             }
-            Node::Mul(nodes) => todo!("{nodes:?}"),
-            Node::Amp(main, amp) => {
-                self.stack.push([0.0; 32]);
-                self.node(amp, delta, osc);
-                let amp = self.stack.pop().unwrap();
-
-                *osc += 1;
-                self.node(main, delta, osc);
-                for (main, amp) in
-                    self.stack.last_mut().unwrap().iter_mut().zip(amp.iter())
+            Op::Far { input, limit } => {
+                for ((out, sample), limit) in
+                    output.iter_mut().zip(inputs[*input]).zip(inputs[*limit])
                 {
-                    *main *= amp;
+                    let limit = limit.abs();
+
+                    *out = if sample < 0.0 {
+                        sample.min(-limit)
+                    } else {
+                        sample.max(limit)
+                    };
                 }
             }
-            Node::File(bytes) => todo!("{:?}", bytes),
         }
     }
 }
 
+/// Get the next sample of seeded noise, restarting the sequence whenever the
+/// key changes
+fn noise<G: Generator>(state: &mut Option<(u32, G)>, key: f32) -> f32 {
+    let key = key.to_bits();
+
+    if state.as_ref().map_or(true, |(seed, _)| *seed != key) {
+        *state = Some((key, G::with_seed(key)));
+    }
+
+    state.as_mut().unwrap().1.step().into()
+}
+
+/// Calculate oscillator sample at phase, returning the sample and frequency
+fn osc(op: &Op, inputs: &[[f32; 32]], j: usize, phase: f32) -> (f32, f32) {
+    match *op {
+        Op::Sine { hz } => {
+            (libm::cosf(phase * core::f32::consts::TAU), inputs[hz][j])
+        }
+        Op::Ramp { hz, curve } => {
+            (ramp(phase, inputs[curve][j]), inputs[hz][j])
+        }
+        Op::Pulse { hz, duty, alias } => (
+            pulse(phase, inputs[duty][j], inputs[alias][j]),
+            inputs[hz][j],
+        ),
+        ref op => unreachable!("{op:?}"),
+    }
+}
+
+/// Calculate ramp wave sample at phase
+fn ramp(phase: f32, curve: f32) -> f32 {
+    let i = 1.0 - (phase * 2.0);
+
+    if i.is_sign_negative() {
+        let v = i + 1.0;
+
+        v - v * curve * (v - 1.0) - 1.0
+    } else {
+        let w = 1.0 - i;
+
+        1.0 - w + w * curve * (w - 1.0)
+    }
+}
+
+/// Calculate pulse wave sample at phase
+fn pulse(phase: f32, duty: f32, alias: f32) -> f32 {
+    let sa = (alias * 0.5) + 0.5; // size of alias / phase
+    let sp = (duty * 0.5) + 0.5; // size of positive (+) / phase
+    let sn = 1.0 - sp; // size of negative (+) / phase
+    let dc = sp * 0.5; // center of descent alias / phase
+    let ac = 1.0 - dc; // center of ascent alias / phase
+    let lc = dc + (ac - dc) * 0.5; // center of (-1) / phase
+    let lc = lc + (sa * duty * 0.5); //
+    let pa = sp * sa; // size of positive alias / phase
+    let na = sn * sa; // size of negative alias / phase
+    let db = (sp - pa) * 0.5; // descent begin
+    let ae = 1.0 - db; // ascent end
+    let de = lc - (sn - na) * 0.5; // descent end
+    let ab = lc + (sn - na) * 0.5; // ascent begin
+
+    if phase < db {
+        // Before descent begin
+        1.0
+    } else if phase < de {
+        // Before descent end
+        let sd = de - db; // size of descent
+
+        1.0 - 2.0 * (phase - db) / sd
+    } else if phase < ab {
+        // Before ascent begin
+        -1.0
+    } else if phase < ae {
+        // Before ascent end
+        let sa = ae - ab; // size of ascent
+
+        -1.0 + 2.0 * (phase - ab) / sa
+    } else {
+        // After ascent end, until center (+) point
+        1.0
+    }
+}
+
 struct SynthIter<'a, 'b>(&'b mut Synth<'a>, u32);
 
 impl Iterator for SynthIter<'_, '_> {
@@ -634,63 +1176,437 @@ impl Iterator for SynthIter<'_, '_> {
     }
 }
 
-/// File parsing
-fn parse<'a>(file: &'a [u8], inst: &'a [u8]) -> File<'a> {
-    let mut inst: [u8; 4] = inst[..4].try_into().unwrap();
-    let mut code = 0;
+#[cfg(test)]
+mod tests {
+    use fon::{chan::Ch32, Audio};
+
+    use super::*;
+
+    /// Render a quarter second of audio at 48 kHz
+    fn render(wave: Wave<'_>) -> Vec<f32> {
+        let mut audio = Audio::<Ch32, 1>::with_silence(48_000, 12_000);
+        let mut synth = Synth::new(wave);
+
+        synth.stream(audio.sink(), &[]);
+        audio.as_f32_slice().to_vec()
+    }
+
+    /// Constant 440.0 hertz signal
+    const HZ: &[u8] = b"\xFF\xFETwAnG\0\0\0\xDC\x43\x04\0\0\0";
+    /// Sine wave at 440.0 hertz
+    const SINE_FILE: &[u8] =
+        b"\xFF\xFETwAnG\0\0\0\xDC\x43\x04\0\0\0\x04\0\0\x02";
 
-    (inst[3], code) = (code, inst[3]);
+    #[test]
+    fn file_root() {
+        assert_eq!(
+            render(Wave::file(SINE_FILE)),
+            render(Wave::sig(440.0).sine()),
+        );
+    }
+
+    #[test]
+    fn file_nested() {
+        assert_eq!(
+            render(Wave::file(HZ).sine()),
+            render(Wave::sig(440.0).sine()),
+        );
+        assert_eq!(
+            render(Wave::mix(&[
+                Wave::file(SINE_FILE),
+                Wave::sig(220.0).saw(),
+                Wave::file(SINE_FILE).amp(&Wave::sig(0.5)),
+            ])),
+            render(Wave::mix(&[
+                Wave::sig(440.0).sine(),
+                Wave::sig(220.0).saw(),
+                Wave::sig(440.0).sine().amp(&Wave::sig(0.5)),
+            ])),
+        );
+    }
 
-    let index: usize = u32::from_le_bytes(inst).try_into().unwrap();
+    /// Render 32-sample chunks of audio at 48 kHz, one for each set of params
+    fn render_params(wave: Wave<'_>, params: &[&[f32]]) -> Vec<f32> {
+        let mut synth = Synth::new(wave);
+        let mut out = Vec::new();
 
-    match code {
-        x if x == Inst::Sig as u8 => {
-            //
+        for params in params {
+            let mut audio = Audio::<Ch32, 1>::with_silence(48_000, 32);
 
-            File::Sig(f32::from_le_bytes(
-                file[index..][..4].try_into().unwrap(),
-            ))
+            synth.stream(audio.sink(), params);
+            out.extend_from_slice(audio.as_f32_slice());
         }
-        x if x == Inst::Mix as u8 => todo!(),
-        x if x == Inst::Sin as u8 => todo!(),
-        x if x == Inst::Rmp as u8 => todo!(),
-        x if x == Inst::Bez as u8 => todo!(),
-        x if x == Inst::Sqr as u8 => todo!(),
-        x if x == Inst::Pul as u8 => todo!(),
-        x if x == Inst::Let as u8 => todo!(),
-        x if x == Inst::Del as u8 => todo!(),
-        x if x == Inst::Var as u8 => todo!(),
-        x if x == Inst::Clp as u8 => todo!(),
-        x if x == Inst::Mul as u8 => todo!(),
-        x if x == Inst::Amp as u8 => todo!(),
-        x if x == Inst::Wht as u8 => todo!(),
-        x if x == Inst::Pnk as u8 => todo!(),
-        x if x == Inst::Min as u8 => todo!(),
-        x if x == Inst::Max as u8 => todo!(),
-        _ => panic!("Invalid instruction"),
+
+        out
     }
-}
 
-/// Append synth output to a `Vec`.
-fn save(node: Node<'_>, out: &mut Vec<u8>) {
-    // Magic number
-    out.extend(b"\xFF\xFETwAnG\0");
+    /// Reference ground -1 multiplication
+    fn mul(waves: &[Vec<f32>]) -> Vec<f32> {
+        (0..waves[0].len())
+            .map(|i| {
+                let product = waves
+                    .iter()
+                    .fold(1.0, |product, wave| product * (wave[i] + 1.0) * 0.5);
+
+                product * 2.0 - 1.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mul_exact() {
+        const SINE: Wave = Wave::sig(440.0).sine();
+        const SAW: Wave = Wave::sig(110.0).saw();
+        const RECT: Wave = Wave::sig(3.0).rect(&Wave::sig(0.5));
+
+        assert_eq!(
+            render(Wave::mul(&[SINE, SAW])),
+            mul(&[render(SINE), render(SAW)]),
+        );
+        assert_eq!(
+            render(Wave::mul(&[SINE, SAW, RECT])),
+            mul(&[render(SINE), render(SAW), render(RECT)]),
+        );
+    }
+
+    #[test]
+    fn mul_ground() {
+        const SINE: Wave = Wave::sig(440.0).sine();
+
+        assert!(render(Wave::mul(&[SINE, Wave::sig(-1.0)]))
+            .iter()
+            .all(|sample| *sample == -1.0));
+        assert!(render(Wave::mul(&[SINE, Wave::sig(1.0)]))
+            .iter()
+            .zip(render(SINE))
+            .all(|(sample, sine)| (sample - sine).abs() <= f32::EPSILON));
+        assert_eq!(render(Wave::mul(&[])), render(Wave::sig(1.0)));
+    }
+
+    #[test]
+    fn mul_nested() {
+        const SINE: Wave = Wave::sig(440.0).sine();
+        const SQUARE: Wave = Wave::sig(4.0).sq();
+        const MUL: Wave = Wave::mul(&[SINE, SQUARE]);
+        const GAIN: Wave = Wave::sig(0.5);
+        const SAW: Wave = Wave::sig(110.0).saw();
+
+        let product = mul(&[render(SINE), render(SQUARE)]);
+        let saw = render(SAW);
+
+        assert_eq!(
+            render(Wave::mix(&[MUL.amp(&GAIN), SAW])),
+            product
+                .iter()
+                .zip(saw)
+                .map(|(mul, saw)| 0.0 + mul * 0.5 + saw)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            render(Wave::mul(&[MUL, SAW])),
+            mul(&[product, render(SAW)]),
+        );
+        assert_eq!(
+            render(Wave::file(&Wave::mix(&[MUL, SAW]).to_bytes())),
+            render(Wave::mix(&[MUL, SAW])),
+        );
+    }
+
+    #[test]
+    fn var_count() {
+        const SINE: Wave = Wave::var_unclamped(2).sine();
+        const WAVE: Wave = Wave::mix(&[SINE, Wave::var(0)]);
 
-    match node {
-        Node::Sig(v) => {
-            let bytes = v.to_le_bytes();
-            let index = u32::try_from(out.len()).unwrap().to_le_bytes();
+        assert_eq!(Synth::new(Wave::sig(440.0).sine()).params(), 0);
+        assert_eq!(Synth::new(Wave::var(0)).params(), 1);
+        assert_eq!(Synth::new(WAVE).params(), 3);
+        assert_eq!(Synth::new(Wave::file(&WAVE.to_bytes())).params(), 3);
+    }
+
+    #[test]
+    fn var_smoothing() {
+        let out = render_params(Wave::var(0), &[&[0.5], &[-0.5], &[-0.5]]);
 
-            out.extend(bytes);
+        assert!(out[..32].iter().all(|sample| *sample == 0.5));
+        for (i, sample) in out[32..64].iter().enumerate() {
+            assert_eq!(*sample, 0.5 - i as f32 / 32.0);
         }
-        Node::Mix(nodes) => todo!("{:?}", nodes),
-        Node::Sine { hz } => todo!("{:?}", hz),
-        Node::Ramp { hz, curve } => todo!("{:?} {:?}", hz, curve),
-        Node::Pulse { hz, duty, alias } => {
-            todo!("{:?} {:?} {:?}", hz, duty, alias)
+        assert!(out[64..].iter().all(|sample| *sample == -0.5));
+    }
+
+    #[test]
+    fn var_clamp() {
+        let clamped = render_params(Wave::var(0), &[&[2.0], &[-2.0]]);
+        let unclamped =
+            render_params(Wave::var_unclamped(0), &[&[2.0], &[-2.0]]);
+
+        assert!(clamped[..32].iter().all(|sample| *sample == 1.0));
+        assert!(clamped[56..].iter().all(|sample| *sample == -1.0));
+        assert!(unclamped[..32].iter().all(|sample| *sample == 2.0));
+        assert_eq!(unclamped[48], 0.0);
+    }
+
+    #[test]
+    fn var_drives_wave() {
+        const WAVE: Wave = Wave::var_unclamped(1).sine().amp(&Wave::var(0));
+        const PARAMS: [&[f32]; 4] = [&[0.5, 440.0]; 4];
+
+        assert_eq!(
+            render_params(WAVE, &PARAMS),
+            render(Wave::sig(440.0).sine().amp(&Wave::sig(0.5)))[..128],
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn var_missing() {
+        render_params(Wave::var(1), &[&[0.0]]);
+    }
+
+    #[test]
+    fn noise_seeded() {
+        let mut white = White::new();
+        let mut pink = Pink::with_seed(4.0f32.to_bits());
+
+        assert_eq!(
+            render(Wave::ZERO.white()),
+            (0..12_000)
+                .map(|_| white.step().into())
+                .collect::<Vec<f32>>(),
+        );
+        assert_eq!(
+            render(Wave::sig(4.0).pink()),
+            (0..12_000)
+                .map(|_| pink.step().into())
+                .collect::<Vec<f32>>(),
+        );
+        assert_ne!(render(Wave::ZERO.white()), render(Wave::MAX.white()));
+        assert_ne!(render(Wave::ZERO.pink()), render(Wave::MAX.pink()));
+    }
+
+    #[test]
+    fn noise_key_change() {
+        const KEY: Wave = Wave::sig(8.0).sq();
+
+        let keys = render(KEY);
+        let mut white = White::with_seed(keys[0].to_bits());
+        let expected: Vec<f32> = (0..keys.len())
+            .map(|i| {
+                if i > 0 && keys[i] != keys[i - 1] {
+                    white = White::with_seed(keys[i].to_bits());
+                }
+                white.step().into()
+            })
+            .collect();
+        let white = render(KEY.white());
+
+        assert_eq!(white, expected);
+        // Returning to the first key restarts the same sequence
+        let restart = (1..keys.len())
+            .find(|i| keys[*i] == keys[0] && keys[i - 1] != keys[0])
+            .unwrap();
+
+        assert_eq!(white[..1_000], white[restart..][..1_000]);
+    }
+
+    #[test]
+    fn noise_round_trip() {
+        const WAVE: Wave = Wave::mix(&[
+            Wave::ZERO.white(),
+            Wave::sig(7.0).pink().amp(&Wave::sig(0.5)),
+        ]);
+
+        assert_eq!(render(Wave::file(&WAVE.to_bytes())), render(WAVE));
+    }
+
+    #[test]
+    fn phase_offset() {
+        const SINE: Wave = Wave::sig(440.0).sine();
+        const SAW: Wave = Wave::sig(440.0).saw();
+        const RECT: Wave = Wave::sig(440.0).rect(&Wave::sig(0.5));
+
+        assert_eq!(render(SINE.phase(Wave::ZERO)), render(SINE));
+        assert_eq!(render(SAW.phase(Wave::ZERO)), render(SAW));
+        assert_eq!(render(RECT.phase(Wave::ZERO)), render(RECT));
+
+        for (shifted, sine) in render(SINE.phase(&Wave::sig(0.5)))
+            .into_iter()
+            .zip(render(SINE))
+        {
+            assert!((shifted + sine).abs() < 0.0001);
         }
-        Node::Mul(nodes) => todo!("{nodes:?}"),
-        Node::Amp(main, amp) => todo!("{main:?} {amp:?}"),
-        Node::File(bytes) => todo!("{:?}", bytes),
+    }
+
+    #[test]
+    fn phase_modulation() {
+        const MODULATOR: Wave = Wave::sig(660.0).sine();
+        const WAVE: Wave = Wave::sig(440.0).sine().phase(&MODULATOR);
+
+        let delta = (48_000.0f64).recip() as f32;
+        let mut phase = 0.0f32;
+        let expected: Vec<f32> = render(MODULATOR)
+            .into_iter()
+            .map(|offset| {
+                let mut shifted = (phase + offset) % 1.0;
+
+                if shifted < 0.0 {
+                    shifted += 1.0;
+                }
+                phase = (phase + delta * 440.0) % 1.0;
+                libm::cosf(shifted * core::f32::consts::TAU)
+            })
+            .collect();
+
+        assert_eq!(render(WAVE), expected);
+        assert_eq!(render(Wave::file(&WAVE.to_bytes())), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn phase_not_oscillator() {
+        let _ = Wave::sig(440.0).phase(Wave::ZERO);
+    }
+
+    /// Apply a per-sample operation to two rendered waves
+    fn zip(a: Vec<f32>, b: Vec<f32>, f: fn(Ch32, Ch32) -> Ch32) -> Vec<f32> {
+        a.into_iter()
+            .zip(b)
+            .map(|(a, b)| f(a.into(), b.into()).into())
+            .collect()
+    }
+
+    #[test]
+    fn limits() {
+        use crate::ops;
+
+        const SINE: Wave = Wave::sig(440.0).sine();
+        const SAW: Wave = Wave::sig(110.0).saw();
+
+        let (sine, saw) = (render(SINE), render(SAW));
+
+        assert_eq!(
+            render(Wave::min(&[SINE, SAW])),
+            zip(sine.clone(), saw.clone(), |a, b| ops::Min.step(a, b)),
+        );
+        assert_eq!(
+            render(Wave::max(&[SINE, SAW])),
+            zip(sine.clone(), saw.clone(), |a, b| ops::Max.step(a, b)),
+        );
+        assert_eq!(
+            render(SINE.near(&SAW)),
+            zip(sine.clone(), saw.clone(), |a, b| ops::Near.step(a, b)),
+        );
+        assert_eq!(
+            render(SINE.far(&SAW)),
+            zip(sine, saw, |a, b| ops::Far.step(a, b)),
+        );
+    }
+
+    #[test]
+    fn limit_lists() {
+        const SINE: Wave = Wave::sig(440.0).sine();
+        const SAW: Wave = Wave::sig(110.0).saw();
+        const SQUARE: Wave = Wave::sig(55.0).sq();
+
+        assert_eq!(
+            render(Wave::min(&[SINE, SAW, SQUARE])),
+            render(Wave::min(&[Wave::min(&[SINE, SAW]), SQUARE])),
+        );
+        assert_eq!(
+            render(Wave::max(&[SINE, SAW, SQUARE])),
+            render(Wave::max(&[Wave::max(&[SINE, SAW]), SQUARE])),
+        );
+        assert_eq!(render(Wave::min(&[SINE])), render(SINE));
+        assert_eq!(render(Wave::min(&[])), render(Wave::sig(0.0)));
+        assert_eq!(render(Wave::max(&[])), render(Wave::sig(0.0)));
+    }
+
+    #[test]
+    fn clip() {
+        const LOUD: Wave = Wave::sig(440.0).sine().amp(&Wave::sig(2.0));
+
+        assert_eq!(
+            render(LOUD.clip()),
+            render(LOUD)
+                .into_iter()
+                .map(|x| x.clamp(-1.0, 1.0))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn limits_round_trip() {
+        const SINE: Wave = Wave::sig(440.0).sine();
+        const GAIN: Wave = Wave::sig(0.5);
+        const WAVE: Wave = Wave::mix(&[
+            Wave::min(&[SINE, GAIN]),
+            Wave::max(&[SINE, Wave::sig(0.0), GAIN]),
+            SINE.near(&GAIN).clip(),
+            SINE.far(&Wave::sig(110.0).saw()),
+            Wave::min(&[]),
+        ]);
+
+        assert_eq!(render(Wave::file(&WAVE.to_bytes())), render(WAVE));
+    }
+
+    #[test]
+    fn round_trip() {
+        const SINE: Wave = Wave::sig(220.0).sine();
+        const GAINS: [Wave; 3] =
+            [Wave::sig(0.5), Wave::sig(0.25), Wave::sig(1.0)];
+        const PIANO: Wave = Wave::mix(&[
+            SINE.amp(&GAINS[0]),
+            SINE.amp(&GAINS[1]),
+            SINE.amp(&GAINS[2]),
+        ]);
+        const WAVES: [Wave; 5] = [
+            PIANO,
+            Wave::sig(440.0).trap(&Wave::sig(0.5), Wave::ZERO),
+            Wave::sig(440.0).ramp(&SINE).amp(&PIANO),
+            Wave::sig(220.0).rect(&SINE),
+            Wave::mix(&[Wave::file(SINE_FILE), Wave::file(SINE_FILE).inv()]),
+        ];
+
+        for wave in WAVES {
+            let bytes = wave.to_bytes();
+
+            assert_eq!(render(Wave::file(&bytes)), render(wave));
+        }
+    }
+
+    /// Stream fed audio through a waveform, one 32-sample chunk at a time
+    fn render_fed(wave: Wave<'_>, feed: &[&[f32]], len: usize) -> Vec<f32> {
+        let mut synth = Synth::new(wave);
+        let mut audio = Audio::<Ch32, 1>::with_silence(48_000, len);
+
+        for samples in feed {
+            synth.feed(&Audio::<Ch32, 1>::with_f32_buffer(48_000, *samples));
+        }
+        synth.stream(audio.sink(), &[]);
+        audio.as_f32_slice().to_vec()
+    }
+
+    #[test]
+    fn input() {
+        let recording: Vec<f32> = (0..100).map(|i| i as f32 / 100.0).collect();
+        let (first, second) = recording.split_at(40);
+
+        // Fed audio follows on from earlier audio, then silence
+        let out = render_fed(Wave::input(), &[first, second], 128);
+
+        assert_eq!(out[..100], recording[..]);
+        assert!(out[100..].iter().all(|sample| *sample == 0.0));
+
+        // Processed, and through a twang file
+        const WAVE: Wave = Wave::mix(&[Wave::input(), Wave::input().inv()]);
+        const GAIN: Wave = Wave::input().amp(&Wave::sig(0.5));
+
+        assert!(render_fed(Wave::file(&WAVE.to_bytes()), &[&recording], 100)
+            .iter()
+            .all(|sample| *sample == 0.0));
+        assert_eq!(
+            render_fed(Wave::file(&GAIN.to_bytes()), &[&recording], 100),
+            recording.iter().map(|x| x * 0.5).collect::<Vec<_>>(),
+        );
     }
 }
@@ -0,0 +1,129 @@
+use core::fmt::{Display, Error, Formatter};
+
+/// An error decoding a twang file
+///
+/// Offsets are in bytes from the beginning of the file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TwangFileError {
+    /// File doesn't begin with the magic bytes
+    Magic,
+    /// File has no instructions
+    Empty,
+    /// File ends in the middle of a word or instruction
+    Truncated,
+    /// Unknown opcode
+    Opcode {
+        /// Offset of the instruction
+        offset: usize,
+        /// The unknown opcode
+        opcode: u8,
+    },
+    /// Continuation flag is invalid for the instruction
+    Continuation {
+        /// Offset of the word with the flag
+        offset: usize,
+    },
+    /// Backreference isn't divisible by 4
+    Misaligned {
+        /// Offset of the word with the reference
+        offset: usize,
+    },
+    /// Backreference points before the beginning of the file
+    OutOfRange {
+        /// Offset of the word with the reference
+        offset: usize,
+    },
+    /// Backreference points into the instruction it's part of
+    SelfReference {
+        /// Offset of the word with the reference
+        offset: usize,
+    },
+    /// Backreference points to a continuation word in the middle of an
+    /// earlier multi-word instruction
+    NotInstruction {
+        /// Offset of the word with the reference
+        offset: usize,
+    },
+    /// Backreference points to data when it should point to a node
+    NotNode {
+        /// Offset of the word with the reference
+        offset: usize,
+    },
+    /// Backreference points to a node when it should point to data
+    NotData {
+        /// Offset of the word with the reference
+        offset: usize,
+    },
+    /// Phase offset isn't followed by an oscillator instruction
+    Phase {
+        /// Offset of the word that should be an oscillator
+        offset: usize,
+    },
+    /// User input clamp flag isn't a boolean
+    Clamp {
+        /// Offset of the VAR instruction
+        offset: usize,
+    },
+    /// User input identifier is too large (see [`Wave::var()`])
+    ///
+    /// [`Wave::var()`]: crate::next::Wave::var
+    Input {
+        /// Offset of the VAR instruction
+        offset: usize,
+    },
+    /// Reserved index of an INP instruction isn't 0
+    Reserved {
+        /// Offset of the INP instruction
+        offset: usize,
+    },
+}
+
+impl Display for TwangFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        use TwangFileError::*;
+        match *self {
+            Magic => write!(f, "missing twang file magic bytes"),
+            Empty => write!(f, "twang file has no instructions"),
+            Truncated => write!(f, "twang file is truncated"),
+            Opcode { offset, opcode } => {
+                write!(f, "unknown opcode {opcode} at byte {offset}")
+            }
+            Continuation { offset } => {
+                write!(f, "invalid continuation flag at byte {offset}")
+            }
+            Misaligned { offset } => {
+                write!(f, "misaligned backreference at byte {offset}")
+            }
+            OutOfRange { offset } => {
+                write!(f, "out of range backreference at byte {offset}")
+            }
+            SelfReference { offset } => {
+                write!(f, "self-referencing instruction at byte {offset}")
+            }
+            NotInstruction { offset } => {
+                write!(
+                    f,
+                    "reference into the middle of an instruction at byte {offset}",
+                )
+            }
+            NotNode { offset } => {
+                write!(f, "reference to data instead of node at byte {offset}")
+            }
+            NotData { offset } => {
+                write!(f, "reference to node instead of data at byte {offset}")
+            }
+            Phase { offset } => {
+                write!(f, "expected oscillator instruction at byte {offset}")
+            }
+            Clamp { offset } => {
+                write!(f, "invalid input clamp flag at byte {offset}")
+            }
+            Input { offset } => {
+                write!(f, "input identifier out of range at byte {offset}")
+            }
+            Reserved { offset } => {
+                write!(f, "reserved index isn't 0 at byte {offset}")
+            }
+        }
+    }
+}
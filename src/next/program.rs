@@ -0,0 +1,936 @@
+//! Twang file decoding
+
+use alloc::{vec, vec::Vec};
+
+use super::TwangFileError;
+
+/// Magic bytes at the start of every twang file
+pub(super) const MAGIC: &[u8; 8] = b"\xFF\xFETwAnG\0";
+
+/// Number of user input identifiers (VAR inputs are less than this)
+pub(super) const MAX_INPUTS: u32 = 1 << 12;
+
+/// Specially handled indices (+0, +1, -0, -1 signals)
+const SPECIAL: [Op; 4] =
+    [Op::Sig(0.0), Op::Sig(1.0), Op::Sig(-0.0), Op::Sig(-1.0)];
+
+/// A synthesis instruction opcode
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Inst {
+    /// Constant signal
+    Sig = 0,
+    /// Mix (add) audio together
+    Mix = 1,
+    /// Sine wave (default)
+    Sin = 2,
+    /// Phase offset modifier
+    Pho = 3,
+    /// Ramp wave
+    Rmp = 4,
+    /// Ramp wave with bezier curve
+    Bez = 5,
+    /// Square wave
+    Sqr = 6,
+    /// Pulse wave
+    Pul = 7,
+    /// Store audio buffer
+    Let = 8,
+    /// Clear audio buffer - audio buffers should be cleared early, and none
+    /// should be left at the root node.
+    Del = 9,
+    /// Define user input node
+    Var = 10,
+    /// Clip by clamping audio from -1 to 1
+    Clp = 11,
+    /// Multiply waves treating -1 as ground
+    Mul = 12,
+    /// Multiply waves treating 0 as ground
+    Amp = 13,
+    /// White noise
+    Wht = 14,
+    /// Pink noise
+    Pnk = 15,
+    /// Minimum value
+    Min = 16,
+    /// Maximum value
+    Max = 17,
+    /// Limit amplitude to be no farther from zero than another wave
+    Nea = 18,
+    /// Limit amplitude to be no nearer to zero than another wave
+    Far = 19,
+    /// Audio fed into the synth
+    Inp = 20,
+}
+
+impl Inst {
+    /// Look up an instruction by opcode
+    fn new(opcode: u8) -> Option<Self> {
+        use Inst::*;
+
+        Some(match opcode {
+            0 => Sig,
+            1 => Mix,
+            2 => Sin,
+            3 => Pho,
+            4 => Rmp,
+            5 => Bez,
+            6 => Sqr,
+            7 => Pul,
+            8 => Let,
+            9 => Del,
+            10 => Var,
+            11 => Clp,
+            12 => Mul,
+            13 => Amp,
+            14 => Wht,
+            15 => Pnk,
+            16 => Min,
+            17 => Max,
+            18 => Nea,
+            19 => Far,
+            20 => Inp,
+            _ => return None,
+        })
+    }
+}
+
+/// A decoded synthesis instruction.
+///
+/// Inputs are indices of earlier instructions within the same [`Program`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    /// Constant signal
+    Sig(f32),
+    /// Mix (add) audio together
+    Mix(Vec<usize>),
+    /// Sine wave
+    Sine {
+        /// Frequency of the waveform
+        hz: usize,
+    },
+    /// Phase offset modifier for an oscillator
+    Phase {
+        /// Oscillator to offset the phase of
+        osc: usize,
+        /// Phase offset
+        offset: usize,
+    },
+    /// Ramp wave
+    Ramp {
+        /// Frequency of the waveform
+        hz: usize,
+        /// Amount of bezier curving to apply; 0 for sawtooth wave
+        curve: usize,
+    },
+    /// Pulse wave
+    Pulse {
+        /// Frequency of the waveform
+        hz: usize,
+        /// Duty cycle; 0 for square wave
+        duty: usize,
+        /// Alias; -1 for no aliasing (pure pulse wave), 0 for trapazoid wave,
+        /// and 1 for triangle wave
+        alias: usize,
+    },
+    /// Store audio buffer
+    Let(usize),
+    /// Clear audio buffer
+    Del(usize),
+    /// User input
+    Var {
+        /// Unique identifier for the input
+        input: u32,
+        /// Whether or not to clamp the input from -1 to 1
+        clamp: bool,
+    },
+    /// Clip by clamping audio from -1 to 1
+    Clip(usize),
+    /// Multiply waves treating -1 as ground
+    Mul(Vec<usize>),
+    /// Multiply waves treating 0 as ground
+    Amp(Vec<usize>),
+    /// White noise
+    White {
+        /// Random key
+        key: usize,
+    },
+    /// Pink noise
+    Pink {
+        /// Random key
+        key: usize,
+    },
+    /// Minimum value
+    Min(Vec<usize>),
+    /// Maximum value
+    Max(Vec<usize>),
+    /// Limit amplitude to be no farther from zero than another wave
+    Near {
+        /// Wave to limit
+        input: usize,
+        /// Amplitude limit (sign is ignored)
+        limit: usize,
+    },
+    /// Limit amplitude to be no nearer to zero than another wave
+    Far {
+        /// Wave to limit
+        input: usize,
+        /// Amplitude limit (sign is ignored)
+        limit: usize,
+    },
+    /// Audio fed into the synth
+    Input,
+}
+
+impl Op {
+    /// Get the indices of all inputs
+    pub(super) fn inputs(&self) -> Vec<usize> {
+        self.clone().inputs_mut().into_iter().map(|x| *x).collect()
+    }
+
+    /// Get mutable references to the indices of all inputs
+    pub(super) fn inputs_mut(&mut self) -> Vec<&mut usize> {
+        use Op::*;
+        match self {
+            Sig(_) | Var { .. } | Input => Vec::new(),
+            Mix(nodes) | Mul(nodes) | Amp(nodes) | Min(nodes) | Max(nodes) => {
+                nodes.iter_mut().collect()
+            }
+            Sine { hz } => vec![hz],
+            Phase { osc, offset } => vec![osc, offset],
+            Ramp { hz, curve } => vec![hz, curve],
+            Pulse { hz, duty, alias } => vec![hz, duty, alias],
+            Let(node) | Del(node) | Clip(node) => vec![node],
+            White { key } | Pink { key } => vec![key],
+            Near { input, limit } | Far { input, limit } => vec![input, limit],
+        }
+    }
+}
+
+/// A synthesis instruction graph.
+///
+/// The first four instructions are always the specially handled +0, +1, -0
+/// and -1 signals.  Instructions only reference earlier instructions, and the
+/// final instruction is the root node of the synthesis tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program(Vec<Op>);
+
+impl Default for Program {
+    fn default() -> Self {
+        Self(Vec::from(SPECIAL))
+    }
+}
+
+impl Program {
+    /// Decode a twang file.
+    pub fn parse(file: &[u8]) -> Result<Self, TwangFileError> {
+        let words = file.strip_prefix(MAGIC).ok_or(TwangFileError::Magic)?;
+
+        if words.len() % 4 != 0 {
+            return Err(TwangFileError::Truncated);
+        }
+
+        if words.is_empty() {
+            return Err(TwangFileError::Empty);
+        }
+
+        let words: Vec<[u8; 4]> = words
+            .chunks_exact(4)
+            .map(|word| word.try_into().unwrap())
+            .collect();
+
+        Parser::new(&words).parse()
+    }
+
+    /// Get the list of instructions, ending with the root node.
+    pub fn ops(&self) -> &[Op] {
+        &self.0
+    }
+
+    /// Get the index of the root node.
+    pub fn root(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    /// Append an instruction, returning its index.
+    pub(super) fn push(&mut self, op: Op) -> usize {
+        self.0.push(op);
+        self.root()
+    }
+
+    /// Append all instructions of another program, returning the index of its
+    /// root node.
+    pub(super) fn splice(&mut self, other: &Self) -> usize {
+        let offset = self.0.len() - SPECIAL.len();
+
+        for op in &other.0[SPECIAL.len()..] {
+            let mut op = op.clone();
+
+            for input in op.inputs_mut() {
+                if *input >= SPECIAL.len() {
+                    *input += offset;
+                }
+            }
+
+            self.0.push(op);
+        }
+
+        self.root()
+    }
+}
+
+/// Twang file parser
+struct Parser<'a> {
+    /// Instruction and data words following the magic bytes
+    words: &'a [[u8; 4]],
+    /// Which words are data referenced by SIG and VAR instructions
+    is_data: Vec<bool>,
+    /// Instruction index for each word that begins an instruction
+    starts: Vec<Option<usize>>,
+    /// Decoded instructions
+    program: Program,
+}
+
+impl<'a> Parser<'a> {
+    fn new(words: &'a [[u8; 4]]) -> Self {
+        // Data always directly precedes the first instruction that reads it,
+        // which lets it be told apart from instructions back to front.
+        let mut is_data = vec![false; words.len()];
+
+        for i in (0..words.len().saturating_sub(1)).rev() {
+            let [.., code] = words[i + 1];
+
+            is_data[i] = !is_data[i + 1]
+                && index(words[i + 1]) == 4
+                && (code == Inst::Sig as u8 || code == Inst::Var as u8);
+        }
+
+        Self {
+            words,
+            is_data,
+            starts: vec![None; words.len()],
+            program: Program::default(),
+        }
+    }
+
+    fn parse(mut self) -> Result<Program, TwangFileError> {
+        let mut word = 0;
+
+        while word < self.words.len() {
+            if self.is_data[word] {
+                word += 1;
+                continue;
+            }
+
+            let (op, len) = self.inst(word, word)?;
+
+            self.starts[word] = Some(op);
+            word += len;
+        }
+
+        Ok(self.program)
+    }
+
+    /// Decode instruction at word, returning index of op and number of words
+    ///
+    /// `begin` is the first word of the instruction (before any modifiers).
+    fn inst(
+        &mut self,
+        begin: usize,
+        word: usize,
+    ) -> Result<(usize, usize), TwangFileError> {
+        let [.., code] = self.word(word)?;
+        let inst = Inst::new(code).ok_or(TwangFileError::Opcode {
+            offset: offset(word),
+            opcode: code,
+        })?;
+        let one = |op| (op, 1);
+        let (op, len) = match inst {
+            Inst::Sig => match self.data(begin, word)? {
+                Ok(bytes) => one(Op::Sig(f32::from_le_bytes(bytes))),
+                Err(special) => one(SPECIAL[special].clone()),
+            },
+            Inst::Mix => {
+                let (nodes, len) = self.list(begin, word)?;
+
+                (Op::Mix(nodes), len)
+            }
+            Inst::Sin => one(Op::Sine {
+                hz: self.node(begin, word)?,
+            }),
+            Inst::Pho => {
+                let offset = self.node(begin, word)?;
+                let [.., code] = self.word(word + 1)?;
+
+                if !matches!(
+                    Inst::new(code),
+                    Some(
+                        Inst::Sin
+                            | Inst::Rmp
+                            | Inst::Bez
+                            | Inst::Sqr
+                            | Inst::Pul
+                    ),
+                ) {
+                    return Err(TwangFileError::Phase {
+                        offset: self::offset(word + 1),
+                    });
+                }
+
+                let (osc, len) = self.inst(begin, word + 1)?;
+
+                (Op::Phase { osc, offset }, len + 1)
+            }
+            Inst::Rmp => one(Op::Ramp {
+                hz: self.node(begin, word)?,
+                curve: 0,
+            }),
+            Inst::Bez => {
+                let hz = self.node(begin, word)?;
+                let curve = self.node(begin, word + 1)?;
+
+                self.end(word + 1)?;
+                (Op::Ramp { hz, curve }, 2)
+            }
+            Inst::Sqr => one(Op::Pulse {
+                hz: self.node(begin, word)?,
+                duty: 0,
+                alias: 3,
+            }),
+            Inst::Pul => {
+                let hz = self.node(begin, word)?;
+                let duty = self.node(begin, word + 1)?;
+
+                if self.more(word + 1)? {
+                    let alias = self.node(begin, word + 2)?;
+
+                    self.end(word + 2)?;
+                    (Op::Pulse { hz, duty, alias }, 3)
+                } else {
+                    (Op::Pulse { hz, duty, alias: 3 }, 2)
+                }
+            }
+            Inst::Let => one(Op::Let(self.node(begin, word)?)),
+            Inst::Del => one(Op::Del(self.node(begin, word)?)),
+            Inst::Var => {
+                let Ok([a, b, c, clamp]) = self.data(begin, word)? else {
+                    return Err(TwangFileError::NotData {
+                        offset: offset(word),
+                    });
+                };
+
+                if clamp > 1 {
+                    return Err(TwangFileError::Clamp {
+                        offset: offset(word),
+                    });
+                }
+
+                let input = u32::from_le_bytes([a, b, c, 0]);
+
+                // Synths allocate a parameter for every identifier up to it
+                if input >= MAX_INPUTS {
+                    return Err(TwangFileError::Input {
+                        offset: offset(word),
+                    });
+                }
+
+                one(Op::Var {
+                    input,
+                    clamp: clamp == 1,
+                })
+            }
+            Inst::Clp => one(Op::Clip(self.node(begin, word)?)),
+            Inst::Mul => {
+                let (nodes, len) = self.list(begin, word)?;
+
+                (Op::Mul(nodes), len)
+            }
+            Inst::Amp => {
+                let (nodes, len) = self.list(begin, word)?;
+
+                (Op::Amp(nodes), len)
+            }
+            Inst::Wht => one(Op::White {
+                key: self.node(begin, word)?,
+            }),
+            Inst::Pnk => one(Op::Pink {
+                key: self.node(begin, word)?,
+            }),
+            Inst::Min => {
+                let (nodes, len) = self.list(begin, word)?;
+
+                (Op::Min(nodes), len)
+            }
+            Inst::Max => {
+                let (nodes, len) = self.list(begin, word)?;
+
+                (Op::Max(nodes), len)
+            }
+            Inst::Nea | Inst::Far => {
+                let input = self.node(begin, word)?;
+                let limit = self.node(begin, word + 1)?;
+
+                self.end(word + 1)?;
+                match inst {
+                    Inst::Nea => (Op::Near { input, limit }, 2),
+                    _ => (Op::Far { input, limit }, 2),
+                }
+            }
+            Inst::Inp => {
+                if index(self.word(word)?) != 0 {
+                    return Err(TwangFileError::Reserved {
+                        offset: offset(word),
+                    });
+                }
+
+                one(Op::Input)
+            }
+        };
+
+        Ok((self.program.push(op), len))
+    }
+
+    /// Decode a list of two or more nodes, returning nodes and number of words
+    fn list(
+        &self,
+        begin: usize,
+        word: usize,
+    ) -> Result<(Vec<usize>, usize), TwangFileError> {
+        let mut nodes = Vec::from([self.node(begin, word)?]);
+
+        loop {
+            let len = nodes.len();
+
+            nodes.push(self.node(begin, word + len)?);
+
+            if !self.more(word + len)? {
+                break Ok((nodes, len + 1));
+            }
+        }
+    }
+
+    /// Get a word
+    fn word(&self, word: usize) -> Result<[u8; 4], TwangFileError> {
+        self.words
+            .get(word)
+            .copied()
+            .ok_or(TwangFileError::Truncated)
+    }
+
+    /// Check the continuation flag of a word
+    fn more(&self, word: usize) -> Result<bool, TwangFileError> {
+        match self.word(word)? {
+            [.., 0] => Ok(false),
+            [.., 1] => Ok(true),
+            _ => Err(TwangFileError::Continuation {
+                offset: offset(word),
+            }),
+        }
+    }
+
+    /// Require the continuation flag of a word to end the instruction
+    fn end(&self, word: usize) -> Result<(), TwangFileError> {
+        if self.more(word)? {
+            return Err(TwangFileError::Continuation {
+                offset: offset(word),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Get the index of the word the word at `word` references (or the special
+    /// index)
+    fn target(
+        &self,
+        begin: usize,
+        word: usize,
+    ) -> Result<Result<usize, usize>, TwangFileError> {
+        let index = index(self.word(word)?);
+
+        if index < 4 {
+            return Ok(Err(index));
+        }
+
+        if index % 4 != 0 {
+            return Err(TwangFileError::Misaligned {
+                offset: offset(word),
+            });
+        }
+
+        let target =
+            word.checked_sub(index / 4)
+                .ok_or(TwangFileError::OutOfRange {
+                    offset: offset(word),
+                })?;
+
+        if target >= begin {
+            return Err(TwangFileError::SelfReference {
+                offset: offset(word),
+            });
+        }
+
+        Ok(Ok(target))
+    }
+
+    /// Get the node the word at `word` references
+    fn node(&self, begin: usize, word: usize) -> Result<usize, TwangFileError> {
+        match self.target(begin, word)? {
+            Ok(target) if self.is_data[target] => {
+                Err(TwangFileError::NotNode {
+                    offset: offset(word),
+                })
+            }
+            Ok(target) => {
+                self.starts[target].ok_or(TwangFileError::NotInstruction {
+                    offset: offset(word),
+                })
+            }
+            Err(special) => Ok(special),
+        }
+    }
+
+    /// Get the data the word at `word` references (or the special index)
+    fn data(
+        &self,
+        begin: usize,
+        word: usize,
+    ) -> Result<Result<[u8; 4], usize>, TwangFileError> {
+        match self.target(begin, word)? {
+            Ok(target) if self.is_data[target] => Ok(Ok(self.words[target])),
+            Ok(_) => Err(TwangFileError::NotData {
+                offset: offset(word),
+            }),
+            Err(special) => Ok(Err(special)),
+        }
+    }
+}
+
+/// Get the 24-bit index from a word
+fn index(word: [u8; 4]) -> usize {
+    let [a, b, c, _] = word;
+
+    u32::from_le_bytes([a, b, c, 0]).try_into().unwrap()
+}
+
+/// Get the byte offset within the file of a word
+fn offset(word: usize) -> usize {
+    MAGIC.len() + word * 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a file from a list of words
+    fn file(words: &[[u8; 4]]) -> Vec<u8> {
+        let mut file = Vec::from(*MAGIC);
+
+        for word in words {
+            file.extend(word);
+        }
+        file
+    }
+
+    /// Build an instruction word
+    fn inst(index: u32, code: u8) -> [u8; 4] {
+        let [a, b, c, _] = index.to_le_bytes();
+
+        [a, b, c, code]
+    }
+
+    #[test]
+    fn special_signals() {
+        for (index, value) in [0.0f32, 1.0, -0.0, -1.0].into_iter().enumerate()
+        {
+            let file = file(&[inst(index as u32, Inst::Sig as u8)]);
+            let program = Program::parse(&file).unwrap();
+
+            assert_eq!(program.root(), 4);
+            assert_eq!(
+                program.ops()[4],
+                Op::Sig(value),
+                "special index {index}",
+            );
+        }
+    }
+
+    #[test]
+    fn data_and_oscillators() {
+        let file = file(&[
+            220.0f32.to_le_bytes(),
+            inst(4, Inst::Sig as u8),
+            inst(4, Inst::Sin as u8),
+            inst(8, Inst::Rmp as u8),
+            inst(12, Inst::Bez as u8),
+            inst(3, 0),
+            inst(20, Inst::Sqr as u8),
+            inst(24, Inst::Pul as u8),
+            inst(1, 0),
+            inst(32, Inst::Pul as u8),
+            inst(2, 1),
+            inst(0, 0),
+        ]);
+        let program = Program::parse(&file).unwrap();
+
+        assert_eq!(
+            &program.ops()[4..],
+            &[
+                Op::Sig(220.0),
+                Op::Sine { hz: 4 },
+                Op::Ramp { hz: 4, curve: 0 },
+                Op::Ramp { hz: 4, curve: 3 },
+                Op::Pulse {
+                    hz: 4,
+                    duty: 0,
+                    alias: 3,
+                },
+                Op::Pulse {
+                    hz: 4,
+                    duty: 1,
+                    alias: 3,
+                },
+                Op::Pulse {
+                    hz: 4,
+                    duty: 2,
+                    alias: 0,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn continuation_lists() {
+        let file = file(&[
+            inst(1, Inst::Sin as u8),
+            inst(3, Inst::Sqr as u8),
+            inst(8, Inst::Mix as u8),
+            inst(8, 1),
+            inst(0, 0),
+            inst(20, Inst::Mul as u8),
+            inst(20, 0),
+            inst(28, Inst::Amp as u8),
+            inst(28, 0),
+            inst(36, Inst::Min as u8),
+            inst(36, 0),
+            inst(44, Inst::Max as u8),
+            inst(44, 1),
+            inst(44, 1),
+            inst(3, 0),
+            inst(60, Inst::Nea as u8),
+            inst(60, 0),
+            inst(68, Inst::Far as u8),
+            inst(1, 0),
+        ]);
+        let program = Program::parse(&file).unwrap();
+
+        assert_eq!(
+            &program.ops()[6..],
+            &[
+                Op::Mix(vec![4, 5, 0]),
+                Op::Mul(vec![4, 5]),
+                Op::Amp(vec![4, 5]),
+                Op::Min(vec![4, 5]),
+                Op::Max(vec![4, 5, 6, 3]),
+                Op::Near { input: 4, limit: 5 },
+                Op::Far { input: 4, limit: 1 },
+            ],
+        );
+    }
+
+    #[test]
+    fn cache_and_modifiers() {
+        let file = file(&[
+            inst(1, Inst::Sin as u8),
+            inst(4, Inst::Let as u8),
+            0.5f32.to_le_bytes(),
+            inst(4, Inst::Sig as u8),
+            inst(4, Inst::Pho as u8),
+            inst(16, Inst::Sin as u8),
+            [7, 0, 0, 1],
+            inst(4, Inst::Var as u8),
+            inst(28, Inst::Del as u8),
+            inst(4, Inst::Clp as u8),
+            inst(12, Inst::Wht as u8),
+            inst(28, Inst::Pnk as u8),
+            inst(0, Inst::Inp as u8),
+        ]);
+        let program = Program::parse(&file).unwrap();
+
+        assert_eq!(
+            &program.ops()[4..],
+            &[
+                Op::Sine { hz: 1 },
+                Op::Let(4),
+                Op::Sig(0.5),
+                Op::Sine { hz: 5 },
+                Op::Phase { osc: 7, offset: 6 },
+                Op::Var {
+                    input: 7,
+                    clamp: true,
+                },
+                Op::Del(5),
+                Op::Clip(10),
+                Op::White { key: 9 },
+                Op::Pink { key: 8 },
+                Op::Input,
+            ],
+        );
+    }
+
+    #[test]
+    fn invalid_magic() {
+        assert_eq!(
+            Program::parse(b"\xFF\xFETwAnG\x01\x01\0\0\0"),
+            Err(TwangFileError::Magic),
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        use TwangFileError::*;
+
+        let sig = |index| inst(index, Inst::Sig as u8);
+        let sin = |index| inst(index, Inst::Sin as u8);
+        let cases: &[(&[[u8; 4]], TwangFileError)] = &[
+            (&[], Empty),
+            (
+                &[sin(1), [4, 0, 0, 0xFF]],
+                Opcode {
+                    offset: 12,
+                    opcode: 255,
+                },
+            ),
+            (&[sin(5)], Misaligned { offset: 8 }),
+            (&[sin(8)], OutOfRange { offset: 8 }),
+            (
+                &[sin(1), inst(4, Inst::Pho as u8), sin(4)],
+                SelfReference { offset: 16 },
+            ),
+            (
+                &[sin(1), inst(4, Inst::Mix as u8), inst(8, 0), sin(4)],
+                NotInstruction { offset: 20 },
+            ),
+            (
+                &[sin(1), inst(4, Inst::Bez as u8), inst(1, 0), sin(4)],
+                NotInstruction { offset: 20 },
+            ),
+            (
+                &[
+                    sin(1),
+                    inst(4, Inst::Pul as u8),
+                    inst(8, 1),
+                    inst(1, 0),
+                    sin(8),
+                ],
+                NotInstruction { offset: 24 },
+            ),
+            (
+                &[1.0f32.to_le_bytes(), sig(4), sin(8)],
+                NotNode { offset: 16 },
+            ),
+            (&[sin(1), sin(1), sig(8)], NotData { offset: 16 }),
+            (
+                &[sin(1), inst(4, Inst::Mix as u8), inst(8, 2)],
+                Continuation { offset: 16 },
+            ),
+            (
+                &[inst(1, Inst::Bez as u8), inst(1, 1)],
+                Continuation { offset: 12 },
+            ),
+            (&[inst(1, Inst::Mix as u8), inst(0, 1)], Truncated),
+            (
+                &[inst(1, Inst::Pho as u8), inst(0, Inst::Let as u8)],
+                Phase { offset: 12 },
+            ),
+            (
+                &[[0, 0, 0, 2], inst(4, Inst::Var as u8)],
+                Clamp { offset: 12 },
+            ),
+            (
+                &[[0, 0x10, 0, 0], inst(4, Inst::Var as u8)],
+                Input { offset: 12 },
+            ),
+            (
+                &[[0xFF, 0xFF, 0xFF, 1], inst(4, Inst::Var as u8)],
+                Input { offset: 12 },
+            ),
+            (&[sin(1), inst(4, Inst::Inp as u8)], Reserved { offset: 12 }),
+        ];
+
+        for (words, error) in cases {
+            assert_eq!(Program::parse(&file(words)), Err(*error), "{words:?}");
+        }
+
+        assert_eq!(Program::parse(&file(&[sin(1)])[..9]), Err(Truncated));
+    }
+
+    /// Deterministically corrupt valid files, and make sure decoding never
+    /// panics or produces an invalid program
+    #[test]
+    fn corrupted_corpus() {
+        use crate::next::Wave;
+
+        const SINE: Wave = Wave::sig(220.0).sine();
+        const CORPUS: &[Wave] = &[
+            Wave::sig(0.5),
+            Wave::mix(&[SINE, SINE.amp(&Wave::sig(0.5))]),
+            Wave::sig(440.0).trap(&SINE, Wave::MAX),
+            Wave::sig(110.0).ramp(&Wave::sig(-0.5)),
+            Wave::mul(&[SINE, Wave::sig(55.0).sq()]),
+        ];
+
+        let mut seed = 0x2545_F491_u32;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            seed >> 8
+        };
+        let check = |bytes: &[u8]| {
+            let Ok(program) = Program::parse(bytes) else {
+                return;
+            };
+
+            assert_eq!(&program.ops()[..4], &SPECIAL);
+            for (index, op) in program.ops().iter().enumerate() {
+                assert!(op.inputs().iter().all(|input| *input < index));
+            }
+            // Valid programs can always be re-encoded and decoded again
+            assert!(Program::parse(&program.to_bytes()).is_ok());
+        };
+
+        for wave in CORPUS {
+            let bytes = wave.to_bytes();
+
+            check(&bytes);
+
+            for len in 0..bytes.len() {
+                check(&bytes[..len]);
+            }
+
+            for _ in 0..1000 {
+                let mut bytes = bytes.clone();
+                let index = random() as usize % bytes.len();
+
+                bytes[index] ^= 1 << (random() % 8);
+                check(&bytes);
+
+                let index = random() as usize % bytes.len();
+
+                bytes[index] = random() as u8;
+                check(&bytes);
+            }
+        }
+
+        for _ in 0..1000 {
+            let mut bytes = Vec::from(*MAGIC);
+            let len = random() % 16;
+
+            for _ in 0..len {
+                let [a, b, c, _] = (random() % 64).to_le_bytes();
+
+                bytes.extend([a, b, c, (random() % 21) as u8]);
+            }
+            check(&bytes);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! Twang file encoding
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use super::program::{Inst, Op, Program, MAGIC};
+
+/// Number of specially handled indices
+const SPECIAL: usize = 4;
+
+/// Where an input of an instruction can be found in the file
+#[derive(Debug, Copy, Clone)]
+enum Target {
+    /// Specially handled index
+    Special(usize),
+    /// Word offset from the magic bytes
+    Word(usize),
+}
+
+impl Program {
+    /// Encode as a twang file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        self.write_to(&mut out);
+        out
+    }
+
+    /// Append twang file to a `Vec`.
+    ///
+    /// Identical subtrees are only written once, and then shared with the LET
+    /// and DEL cache instructions.
+    ///
+    /// # Panics
+    /// If a backreference doesn't fit in 24 bits.
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        let (ops, root) = dedup(self.ops());
+        let mut writer = Writer {
+            start: out.len() + MAGIC.len(),
+            out,
+            uses: vec![0; ops.len()],
+            cache: vec![None; ops.len()],
+            place: vec![None; ops.len()],
+        };
+        let mut used = vec![false; ops.len()];
+
+        writer.out.extend(MAGIC);
+        used[root] = true;
+
+        // Count uses of each node, back to front
+        for index in (SPECIAL..ops.len()).rev() {
+            if !used[index] {
+                continue;
+            }
+
+            for input in inputs(&ops, index) {
+                used[input] = true;
+                writer.uses[input] += 1;
+            }
+        }
+
+        // Oscillators only used for phase offsets are only written inline
+        for (index, used) in used.into_iter().enumerate().skip(SPECIAL) {
+            if used {
+                writer.node(&ops, index);
+            }
+        }
+
+        // Special root nodes still need an instruction
+        if root < SPECIAL {
+            writer.word(Target::Special(root), Inst::Sig as u8);
+        }
+    }
+}
+
+/// Merge identical instructions and remove redundant ones, returning the new
+/// instructions and root index
+fn dedup(ops: &[Op]) -> (Vec<Op>, usize) {
+    let mut canon: Vec<usize> = (0..SPECIAL).collect();
+    let mut out = ops[..SPECIAL].to_vec();
+    let mut keys = BTreeMap::new();
+
+    for op in &ops[SPECIAL..] {
+        let mut op = op.clone();
+
+        for input in op.inputs_mut() {
+            *input = canon[*input];
+        }
+
+        let alias = match op {
+            Op::Let(node) | Op::Del(node) => Some(node),
+            Op::Sig(v) => [0.0f32, 1.0, -0.0, -1.0]
+                .iter()
+                .position(|x| x.to_bits() == v.to_bits()),
+            Op::Mix(ref nodes)
+            | Op::Mul(ref nodes)
+            | Op::Amp(ref nodes)
+            | Op::Min(ref nodes)
+            | Op::Max(ref nodes) => match nodes[..] {
+                [] => Some(match op {
+                    Op::Mul(_) | Op::Amp(_) => 1,
+                    _ => 0,
+                }),
+                [node] => Some(node),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some(alias) = alias {
+            canon.push(alias);
+            continue;
+        }
+
+        let index = *keys.entry(key(&op)).or_insert(out.len());
+
+        if index == out.len() {
+            out.push(op);
+        }
+
+        canon.push(index);
+    }
+
+    (out, canon[ops.len() - 1])
+}
+
+/// Get a key that is equal for identical instructions
+fn key(op: &Op) -> Vec<u64> {
+    let mut key: Vec<u64> = Vec::new();
+
+    key.push(match *op {
+        Op::Sig(v) => u64::from(v.to_bits()) << 8,
+        Op::Var { input, clamp } => {
+            (u64::from(input) << 16) | (u64::from(clamp) << 8) | 1
+        }
+        Op::Mix(_) => 2,
+        Op::Sine { .. } => 3,
+        Op::Phase { .. } => 4,
+        Op::Ramp { .. } => 5,
+        Op::Pulse { .. } => 6,
+        Op::Let(_) => 7,
+        Op::Del(_) => 8,
+        Op::Clip(_) => 9,
+        Op::Mul(_) => 10,
+        Op::Amp(_) => 11,
+        Op::White { .. } => 12,
+        Op::Pink { .. } => 13,
+        Op::Min(_) => 14,
+        Op::Max(_) => 15,
+        Op::Near { .. } => 16,
+        Op::Far { .. } => 17,
+        Op::Input => 18,
+    });
+
+    for input in op.inputs() {
+        key.push(input as u64);
+    }
+
+    key
+}
+
+/// Get the indices of all inputs of a node (including inline oscillator)
+fn inputs(ops: &[Op], index: usize) -> Vec<usize> {
+    match ops[index] {
+        Op::Phase { osc, offset } => {
+            let mut inputs = vec![offset];
+
+            inputs.extend(ops[osc].inputs());
+            inputs
+        }
+        ref op => op.inputs(),
+    }
+}
+
+/// Twang file writer
+struct Writer<'a> {
+    /// Output file
+    out: &'a mut Vec<u8>,
+    /// Byte offset of the first word after the magic bytes
+    start: usize,
+    /// Number of remaining uses of each node
+    uses: Vec<usize>,
+    /// Location of LET instruction for each shared node
+    cache: Vec<Option<usize>>,
+    /// Location of each written node
+    place: Vec<Option<usize>>,
+}
+
+impl Writer<'_> {
+    /// Get the current word offset
+    fn cursor(&self) -> usize {
+        (self.out.len() - self.start) / 4
+    }
+
+    /// Write a single word, returning its location
+    fn word(&mut self, target: Target, code: u8) -> usize {
+        let index = match target {
+            Target::Special(index) => index,
+            Target::Word(word) => (self.cursor() - word) * 4,
+        };
+        let index = u32::try_from(index)
+            .ok()
+            .filter(|index| *index < 1 << 24)
+            .expect("Backreference out of range");
+        let [a, b, c, _] = index.to_le_bytes();
+
+        self.out.extend([a, b, c, code]);
+        self.cursor() - 1
+    }
+
+    /// Write data, then an instruction that reads it
+    fn data(&mut self, data: [u8; 4], inst: Inst) -> usize {
+        let data_word = self.cursor();
+
+        self.out.extend(data);
+        self.word(Target::Word(data_word), inst as u8)
+    }
+
+    /// Write a list of inputs, starting with an opcode
+    fn list(&mut self, targets: &[Target], inst: Inst) -> usize {
+        let (last, rest) = targets.split_last().unwrap();
+        let (first, rest) = rest.split_first().unwrap();
+        let place = self.word(*first, inst as u8);
+
+        for target in rest {
+            self.word(*target, 1);
+        }
+        self.word(*last, 0);
+        place
+    }
+
+    /// Use an input node, writing cache instructions as needed
+    fn target(&mut self, node: usize) -> Target {
+        if node < SPECIAL {
+            return Target::Special(node);
+        }
+
+        let place = self.place[node].unwrap();
+        let Some(cache) = self.cache[node] else {
+            return Target::Word(place);
+        };
+
+        self.uses[node] -= 1;
+
+        if self.uses[node] == 0 {
+            // Last use; clear cache
+            let del = self.cursor();
+
+            self.word(Target::Word(cache), Inst::Del as u8);
+            return Target::Word(del);
+        }
+
+        Target::Word(cache)
+    }
+
+    /// Write a node
+    fn node(&mut self, ops: &[Op], index: usize) {
+        let inputs: Vec<Target> = inputs(ops, index)
+            .into_iter()
+            .map(|input| self.target(input))
+            .collect();
+        let place = match ops[index] {
+            Op::Phase { osc, .. } => {
+                let place = self.word(inputs[0], Inst::Pho as u8);
+
+                self.inst(&ops[osc], &inputs[1..]);
+                place
+            }
+            ref op => self.inst(op, &inputs),
+        };
+
+        self.place[index] = Some(place);
+
+        // Cache shared nodes
+        if self.uses[index] > 1 {
+            self.cache[index] = Some(self.cursor());
+            self.word(Target::Word(place), Inst::Let as u8);
+        }
+    }
+
+    /// Write an instruction, returning its location
+    fn inst(&mut self, op: &Op, inputs: &[Target]) -> usize {
+        match (op, inputs) {
+            (Op::Sig(v), _) => self.data(v.to_le_bytes(), Inst::Sig),
+            (Op::Var { input, clamp }, _) => {
+                let [a, b, c, d] = input.to_le_bytes();
+
+                assert_eq!(d, 0, "Input identifier out of range");
+                self.data([a, b, c, (*clamp).into()], Inst::Var)
+            }
+            (Op::Sine { .. }, &[hz]) => self.word(hz, Inst::Sin as u8),
+            (Op::Ramp { curve: 0, .. }, &[hz, _]) => {
+                self.word(hz, Inst::Rmp as u8)
+            }
+            (Op::Ramp { .. }, &[hz, curve]) => {
+                self.list(&[hz, curve], Inst::Bez)
+            }
+            (
+                Op::Pulse {
+                    duty: 0, alias: 3, ..
+                },
+                &[hz, _, _],
+            ) => self.word(hz, Inst::Sqr as u8),
+            (Op::Pulse { alias: 3, .. }, &[hz, duty, _]) => {
+                self.list(&[hz, duty], Inst::Pul)
+            }
+            (Op::Pulse { .. }, targets) => self.list(targets, Inst::Pul),
+            (Op::Let(_), &[node]) => self.word(node, Inst::Let as u8),
+            (Op::Del(_), &[node]) => self.word(node, Inst::Del as u8),
+            (Op::Clip(_), &[node]) => self.word(node, Inst::Clp as u8),
+            (Op::White { .. }, &[key]) => self.word(key, Inst::Wht as u8),
+            (Op::Pink { .. }, &[key]) => self.word(key, Inst::Pnk as u8),
+            (Op::Mix(_), targets) => self.list(targets, Inst::Mix),
+            (Op::Mul(_), targets) => self.list(targets, Inst::Mul),
+            (Op::Amp(_), targets) => self.list(targets, Inst::Amp),
+            (Op::Min(_), targets) => self.list(targets, Inst::Min),
+            (Op::Max(_), targets) => self.list(targets, Inst::Max),
+            (Op::Near { .. }, targets) => self.list(targets, Inst::Nea),
+            (Op::Far { .. }, targets) => self.list(targets, Inst::Far),
+            (Op::Input, _) => self.word(Target::Special(0), Inst::Inp as u8),
+            (op, _) => unreachable!("{op:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::next::Wave;
+
+    #[test]
+    fn special_root() {
+        assert_eq!(Wave::ZERO.to_bytes(), b"\xFF\xFETwAnG\0\0\0\0\0");
+        assert_eq!(Wave::MIN.to_bytes(), b"\xFF\xFETwAnG\0\x03\0\0\0");
+    }
+
+    #[test]
+    fn shared_subtree() {
+        const SINE: Wave = Wave::sig(440.0).sine();
+        const WAVE: Wave = Wave::mix(&[SINE, SINE]);
+
+        let mut file = Vec::from(*MAGIC);
+
+        file.extend(440.0f32.to_le_bytes());
+        file.extend([4, 0, 0, Inst::Sig as u8]);
+        file.extend([4, 0, 0, Inst::Sin as u8]);
+        file.extend([4, 0, 0, Inst::Let as u8]);
+        file.extend([4, 0, 0, Inst::Del as u8]);
+        file.extend([8, 0, 0, Inst::Mix as u8]);
+        file.extend([8, 0, 0, 0]);
+
+        assert_eq!(WAVE.to_bytes(), file);
+    }
+
+    #[test]
+    fn append() {
+        const WAVE: Wave = Wave::sig(440.0).saw();
+
+        let mut file = Vec::from(*b"head");
+
+        WAVE.write_to(&mut file);
+
+        assert_eq!(&file[..4], b"head");
+        assert_eq!(&file[4..], WAVE.to_bytes());
+    }
+
+    #[test]
+    fn round_trip_program() {
+        const SINE: Wave = Wave::sig(220.0).sine();
+        const WAVE: Wave = Wave::mix(&[
+            SINE.amp(&Wave::sig(0.5)),
+            Wave::sig(440.0).trap(&Wave::sig(0.25), Wave::MAX),
+            Wave::sig(110.0).ramp(&Wave::sig(-0.5)),
+            Wave::sig(110.0).rect(&SINE),
+            Wave::sig(55.0).sq(),
+        ]);
+
+        let bytes = WAVE.to_bytes();
+        let program = Program::parse(&bytes).unwrap();
+
+        assert_eq!(program.to_bytes(), bytes);
+    }
+}
@@ -3,5 +3,72 @@
 mod pink;
 mod white;
 
+use fon::chan::Ch32;
+
 pub use pink::Pink;
 pub use white::White;
+
+/// Seeded noise generator that can be saved into 32-bit states
+pub(crate) trait Generator {
+    /// Number of 32-bit states for [`Generator::load()`] and
+    /// [`Generator::store()`]
+    const STATE_LEN: usize;
+
+    /// Create a new generator, with a seed selecting the sequence
+    fn with_seed(seed: u32) -> Self;
+
+    /// Load from 32-bit states
+    fn load(state: &[u32]) -> Self;
+
+    /// Store into 32-bit states
+    fn store(&self, state: &mut [u32]);
+
+    /// Get next sample from the noise generator
+    fn step(&mut self) -> Ch32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence<G: Generator>(mut generator: G) -> [Ch32; 64] {
+        [(); 64].map(|()| generator.step())
+    }
+
+    fn reload<G: Generator>(generator: &G) -> G {
+        let mut state = [0; 8];
+
+        generator.store(&mut state);
+        G::load(&state[..G::STATE_LEN])
+    }
+
+    #[test]
+    fn seed_zero() {
+        assert_eq!(sequence(White::with_seed(0)), sequence(White::new()));
+        assert_eq!(sequence(Pink::with_seed(0)), sequence(Pink::new()));
+    }
+
+    #[test]
+    fn seeds_differ() {
+        assert_ne!(sequence(White::with_seed(1)), sequence(White::new()));
+        assert_ne!(sequence(Pink::with_seed(1)), sequence(Pink::new()));
+        assert_eq!(
+            sequence(Pink::with_seed(0x5eed41f5)),
+            sequence(Pink::new()),
+        );
+    }
+
+    #[test]
+    fn store_and_load() {
+        let mut white = White::with_seed(7);
+        let mut pink = Pink::with_seed(7);
+
+        for _ in 0..21 {
+            white.step();
+            pink.step();
+        }
+
+        assert_eq!(sequence(reload(&white)), sequence(white));
+        assert_eq!(sequence(reload(&pink)), sequence(pink));
+    }
+}
@@ -1,4 +1,6 @@
-use fon::chan::Ch16;
+use fon::chan::{Ch16, Ch32};
+
+use super::Generator;
 
 // Constants PFIRA and PFIRB
 include!(concat!(env!("OUT_DIR"), "/pink.rs"));
@@ -45,8 +47,19 @@ impl Pink {
     /// Create a new Pink Noise Sampler.
     #[inline(always)]
     pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Create a new Pink Noise Sampler, with a seed selecting the sequence.
+    ///
+    /// A seed of 0 is the same as [`Pink::new()`].
+    #[inline(always)]
+    pub fn with_seed(seed: u32) -> Self {
+        let lfsr = 0x5eed41f5i32 ^ seed as i32;
+
         Self {
-            lfsr: 0x5eed41f5i32,
+            // The shift register must never be all zeros
+            lfsr: if lfsr == 0 { 0x5eed41f5i32 } else { lfsr },
             inc: 0xccc,
             dec: 0xccc,
             accu: 0,
@@ -111,7 +124,7 @@ impl Pink {
 
     /// Get next sample from the noise generator.
     #[inline(always)]
-    pub fn step(&mut self) -> fon::chan::Ch32 {
+    pub fn step(&mut self) -> Ch32 {
         // Different functions for each sample.
         let pink = match self.which {
             _x if _x % 2 != 0 => self.a(), // odd #s
@@ -131,3 +144,39 @@ impl Pink {
         Ch16::new(pink).into()
     }
 }
+
+impl Generator for Pink {
+    const STATE_LEN: usize = 7;
+
+    fn with_seed(seed: u32) -> Self {
+        Self::with_seed(seed)
+    }
+
+    fn load(state: &[u32]) -> Self {
+        Self {
+            lfsr: state[0] as i32,
+            inc: state[1] as i32,
+            dec: state[2] as i32,
+            accu: state[3] as i32,
+            pncnt: state[4] as u8,
+            which: state[5] as u8,
+            bit: state[6] as i32,
+        }
+    }
+
+    fn store(&self, state: &mut [u32]) {
+        state[..7].copy_from_slice(&[
+            self.lfsr as u32,
+            self.inc as u32,
+            self.dec as u32,
+            self.accu as u32,
+            self.pncnt.into(),
+            self.which.into(),
+            self.bit as u32,
+        ]);
+    }
+
+    fn step(&mut self) -> Ch32 {
+        self.step()
+    }
+}
@@ -1,5 +1,7 @@
 use core::num::Wrapping;
-use fon::chan::Ch24;
+use fon::chan::{Ch24, Ch32};
+
+use super::Generator;
 
 const SEQUENCE: u64 = 0xb5ad4eceda1ce2a9;
 
@@ -18,9 +20,20 @@ impl White {
         Self::default()
     }
 
+    /// Create a new White Noise Sampler, with a seed selecting the sequence.
+    ///
+    /// A seed of 0 is the same as [`White::new()`].
+    #[inline(always)]
+    pub fn with_seed(seed: u32) -> Self {
+        Self {
+            x: Wrapping(0),
+            w: Wrapping(u64::from(seed) << 32),
+        }
+    }
+
     /// Get next sample from the noise generator.
     #[inline(always)]
-    pub fn step(&mut self) -> fon::chan::Ch32 {
+    pub fn step(&mut self) -> Ch32 {
         // msws (Middle Square Weyl Sequence) algorithm
         self.x *= self.x;
         self.w += Wrapping(SEQUENCE);
@@ -29,3 +42,34 @@ impl White {
         Ch24::new((self.x.0 as i32) >> 8).into()
     }
 }
+
+impl Generator for White {
+    const STATE_LEN: usize = 4;
+
+    fn with_seed(seed: u32) -> Self {
+        Self::with_seed(seed)
+    }
+
+    fn load(state: &[u32]) -> Self {
+        let join =
+            |lo: u32, hi: u32| Wrapping(u64::from(lo) | u64::from(hi) << 32);
+
+        Self {
+            x: join(state[0], state[1]),
+            w: join(state[2], state[3]),
+        }
+    }
+
+    fn store(&self, state: &mut [u32]) {
+        state[..4].copy_from_slice(&[
+            self.x.0 as u32,
+            (self.x.0 >> 32) as u32,
+            self.w.0 as u32,
+            (self.w.0 >> 32) as u32,
+        ]);
+    }
+
+    fn step(&mut self) -> Ch32 {
+        self.step()
+    }
+}
@@ -1,25 +1,517 @@
 //! A collection of auditory effects.
+//!
+//! # Panics
+//! Effects panic if they are given a `sample_rate` of 0.
 
 #![allow(warnings)]
 
+mod biquad;
 mod clip;
+mod envelope;
 mod far;
 mod gain;
 mod gate;
 mod invert;
+mod ladder;
 mod limiter;
 mod max;
 mod min;
 mod near;
+mod reverb;
 mod room;
+mod svf;
 
+pub use biquad::{Biquad, FilterType};
 pub use clip::Clip;
+pub use envelope::{Curve, Envelope, Segment, Trigger};
 pub use far::Far;
 pub use gain::Gain;
 pub use gate::{Gate, GateParams};
 pub use invert::Invert;
+pub use ladder::Ladder;
 pub use limiter::Limiter;
 pub use max::Max;
 pub use min::Min;
 pub use near::Near;
+pub use reverb::Reverb;
 pub use room::Room;
+pub use svf::{FilterOutput, Svf};
+
+use crate::math::period;
+
+pub(crate) use biquad::biquad;
+pub(crate) use envelope::EnvelopeState;
+pub(crate) use ladder::ladder;
+pub(crate) use svf::svf;
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec::Vec};
+
+    use fon::{
+        chan::{Ch32, Channel},
+        Frame,
+    };
+
+    use super::*;
+
+    const RATES: [u32; 2] = [44_100, 96_000];
+
+    /// Peak amplitude of a 48 kHz sine wave after it settles in a filter
+    fn response(filter: FilterType, cutoff: f32, hz: f32) -> f32 {
+        let mut sine = crate::osc::Sine::new();
+        let mut biquad = Biquad::new(filter);
+
+        (0..48_000)
+            .map(|_| biquad.step(sine.step(hz), cutoff, 0.707).to_f32())
+            .skip(24_000)
+            .fold(0.0, |peak, x| x.abs().max(peak))
+    }
+
+    /// Multimode filter processing function
+    type Multimode = Box<dyn FnMut(Ch32, f32, f32) -> FilterOutput>;
+
+    /// Create new state-variable and ladder filters
+    fn multimodes() -> [Multimode; 2] {
+        let mut svf = Svf::new();
+        let mut ladder = Ladder::new();
+
+        [
+            Box::new(move |x, cutoff, res| svf.step(x, cutoff, res)),
+            Box::new(move |x, cutoff, res| ladder.step(x, cutoff, res)),
+        ]
+    }
+
+    /// Peak amplitudes of the outputs for a 48 kHz sine wave after it settles
+    fn multimode(mut filter: Multimode, hz: f32) -> [f32; 3] {
+        let mut sine = crate::osc::Sine::new();
+        let mut peaks = [0.0f32; 3];
+
+        for i in 0..48_000 {
+            let out = filter(sine.step(hz) * 0.5, 1_000.0, 0.0);
+
+            if i >= 24_000 {
+                let out = [out.lowpass, out.bandpass, out.highpass];
+
+                for (peak, out) in peaks.iter_mut().zip(out) {
+                    *peak = peak.max(out.to_f32().abs() * 2.0);
+                }
+            }
+        }
+
+        peaks
+    }
+
+    #[test]
+    fn multimode_outputs() {
+        for i in 0..2 {
+            let filter = || multimodes().into_iter().nth(i).unwrap();
+            let [lowpass, _, highpass] = multimode(filter(), 50.0);
+
+            assert!(lowpass > 0.8 && highpass < 0.05, "{lowpass} {highpass}");
+
+            let [lowpass, _, highpass] = multimode(filter(), 15_000.0);
+
+            assert!(lowpass < 0.05 && highpass > 0.8, "{lowpass} {highpass}");
+
+            let [_, low, _] = multimode(filter(), 50.0);
+            let [_, band, _] = multimode(filter(), 1_000.0);
+            let [_, high, _] = multimode(filter(), 15_000.0);
+
+            assert!(band > 0.3 && low < 0.1 && high < 0.1, "{band}");
+        }
+    }
+
+    #[test]
+    fn self_oscillation() {
+        for mut filter in multimodes() {
+            let mut last = 0.0;
+            let mut crossings = 0;
+            let mut peak = 0.0f32;
+
+            for i in 0..96_000 {
+                let input = Ch32::new(if i == 0 { 0.5 } else { 0.0 });
+                let out = filter(input, 1_000.0, 1.1).lowpass;
+                let out = out.to_f32();
+
+                assert!(out.is_finite() && out.abs() < 4.0, "{out}");
+
+                // Count cycles in the second second
+                if i >= 48_000 {
+                    if last < 0.0 && out >= 0.0 {
+                        crossings += 1;
+                    }
+                    peak = peak.max(out.abs());
+                }
+                last = out;
+            }
+
+            assert!(peak > 0.05, "{peak}");
+            assert!((950..=1_050).contains(&crossings), "{crossings}");
+        }
+    }
+
+    #[test]
+    fn multimode_sweep() {
+        for mut filter in multimodes() {
+            let mut lfo = crate::osc::Sine::new();
+            let mut saw = crate::osc::Sawtooth::new();
+
+            // Cutoff swept between 20 Hz and 19 kHz at audio rate
+            for _ in 0..48_000 {
+                let cutoff = 9_510.0 + 9_490.0 * lfo.step(220.0).to_f32();
+                let out = filter(saw.step(110.0), cutoff, 0.9);
+
+                for out in [out.lowpass, out.bandpass, out.highpass] {
+                    let out = out.to_f32();
+
+                    assert!(out.is_finite() && out.abs() < 16.0, "{out}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn biquad_sweep() {
+        let mut lfo = crate::osc::Sine::new();
+        let mut saw = crate::osc::Sawtooth::new();
+        let mut biquad = Biquad::new(FilterType::Lowpass);
+
+        // Cutoff swept between 20 Hz and 19 kHz at audio rate, high resonance
+        for _ in 0..48_000 {
+            let cutoff = 9_510.0 + 9_490.0 * lfo.step(220.0).to_f32();
+            let sample = biquad.step(saw.step(110.0), cutoff, 8.0).to_f32();
+
+            assert!(sample.is_finite() && sample.abs() < 16.0, "{sample}");
+        }
+    }
+
+    #[test]
+    fn biquad() {
+        const PEAK: FilterType = FilterType::Peaking { gain: 6.0 };
+        const LOW: FilterType = FilterType::LowShelf { gain: 6.0 };
+        const HIGH: FilterType = FilterType::HighShelf { gain: 6.0 };
+
+        let db6 = libm::powf(10.0, 6.0 / 20.0);
+        let cases = [
+            (FilterType::Lowpass, 100.0, 1.0),
+            (FilterType::Lowpass, 10_000.0, 0.01),
+            (FilterType::Highpass, 100.0, 0.01),
+            (FilterType::Highpass, 10_000.0, 1.0),
+            (FilterType::Bandpass, 1_000.0, 1.0),
+            (FilterType::Bandpass, 50.0, 0.05),
+            (FilterType::Notch, 1_000.0, 0.0),
+            (FilterType::Notch, 50.0, 1.0),
+            (PEAK, 1_000.0, db6),
+            (PEAK, 50.0, 1.0),
+            (LOW, 20.0, db6),
+            (LOW, 15_000.0, 1.0),
+            (HIGH, 20.0, 1.0),
+            (HIGH, 15_000.0, db6),
+        ];
+
+        for (filter, hz, expected) in cases {
+            let peak = response(filter, 1_000.0, hz);
+
+            assert!((peak - expected).abs() < 0.05, "{filter:?} {hz}: {peak}");
+        }
+    }
+
+    #[test]
+    fn gate_release() {
+        for rate in RATES {
+            let mut gate = Gate::with_sample_rate(rate);
+            let mut params = GateParams {
+                input: Ch32::new(1.0),
+                key: Ch32::new(1.0),
+                range: Ch32::new(1.0),
+                open_threshold: Ch32::new(0.5),
+                close_threshold: Ch32::new(0.5),
+                attack: 0.0,
+                hold: 0.0,
+                release: 0.1,
+            };
+
+            assert_eq!(gate.step(&params), Ch32::new(1.0));
+
+            // Count samples until the gate is fully closed
+            params.key = Ch32::new(0.0);
+
+            let samples: u32 = (1..)
+                .find(|_| f32::from(gate.step(&params)) <= 0.0)
+                .unwrap();
+            let expected = rate / 10;
+
+            assert!(samples.abs_diff(expected) <= 2, "{rate}: {samples}");
+        }
+    }
+
+    #[test]
+    fn room_reflection() {
+        for rate in RATES {
+            let mut room = Room::with_sample_rate(rate);
+
+            room.add(Ch32::new(1.0), 0.1, 0.5);
+
+            let samples: u32 =
+                (0..).find(|_| room.step() != Ch32::default()).unwrap();
+
+            assert_eq!(samples, rate / 10);
+        }
+    }
+
+    /// Impulse response of the reverb at 44.1 kHz, with samples checked
+    /// against the reference Freeverb, and an FNV-1a hash of every sample's
+    /// bits.  The expected values are printed by `freeverb/reference.cpp`,
+    /// which runs `revmodel` from `freeverb/Components` over the same input.
+    fn impulse_response(
+        reverb: &mut Reverb,
+        expected: &[(usize, u32, u32)],
+    ) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut expected = expected.iter().peekable();
+
+        for i in 0..16_384 {
+            let input = if i == 0 {
+                Frame::<Ch32, 2>::new(Ch32::new(1.0), Ch32::new(0.5))
+            } else {
+                Frame::default()
+            };
+            let [left, right] =
+                reverb.step(input).channels().map(|x| x.to_f32().to_bits());
+
+            if let Some((_, l, r)) = expected.next_if(|(j, _, _)| *j == i) {
+                assert_eq!([left, right], [*l, *r], "{i}");
+            }
+
+            for bits in [left, right] {
+                hash = (hash ^ u64::from(bits)).wrapping_mul(0x100_0000_01b3);
+            }
+        }
+
+        assert!(expected.next().is_none());
+        hash
+    }
+
+    #[test]
+    fn reverb_reference() {
+        let mut reverb = Reverb::with_sample_rate(44_100);
+        let hash = impulse_response(
+            &mut reverb,
+            &[
+                (0, 0x0000_0000, 0x0000_0000),
+                (1116, 0x3cb8_51eb, 0x0000_0000),
+                (1139, 0x0000_0000, 0x3cb8_51eb),
+                (3000, 0xb6a2_598b, 0xbc38_51e9),
+                (4096, 0x3a18_bd0c, 0xbb86_c5c3),
+                (6000, 0x3ba9_f496, 0x3b3b_d50c),
+                (8192, 0x38fd_943f, 0xbb88_4282),
+                (12000, 0x3b40_391d, 0xb9ad_8124),
+                (16383, 0xba1b_5c38, 0xb865_df9c),
+            ],
+        );
+
+        assert_eq!(hash, 0xa2a5_d888_2fba_0846);
+
+        let mut reverb = Reverb::with_sample_rate(44_100);
+
+        reverb.set_room_size(0.9);
+        reverb.set_damping(0.2);
+        reverb.set_wet(0.8);
+        reverb.set_dry(0.5);
+        reverb.set_width(0.5);
+
+        let hash = impulse_response(
+            &mut reverb,
+            &[
+                (0, 0x3f80_0000, 0x3f00_0000),
+                (1116, 0x3d25_e354, 0x3c5d_2f1b),
+                (1139, 0x3c5d_2f1b, 0x3d25_e354),
+                (3000, 0xbbdd_3015, 0xbca5_e369),
+                (4096, 0xba89_ced6, 0xbbe7_b58c),
+                (6000, 0x3c8e_f89e, 0x3c89_b07a),
+                (8192, 0xbaae_64a8, 0xbb8d_9532),
+                (12000, 0x3cae_f15e, 0x3bdd_2210),
+                (16383, 0xbaf9_8bab, 0x3a96_48fb),
+            ],
+        );
+
+        assert_eq!(hash, 0x099c_febe_7857_2eb8);
+    }
+
+    #[test]
+    fn reverb_sample_rate() {
+        // First reflection of the left and right comb filters
+        for (rate, left, right) in [(44_100, 1116, 1139), (96_000, 2429, 2479)]
+        {
+            let mut reverb = Reverb::with_sample_rate(rate);
+            let mut first = [None; 2];
+
+            for i in 0..4_000 {
+                let input = if i == 0 { 1.0 } else { 0.0 };
+                let input = Frame::<Ch32, 2>::new(input.into(), input.into());
+                let output = *reverb.step(input).channels();
+
+                for (first, output) in first.iter_mut().zip(output) {
+                    if first.is_none() && output != Ch32::default() {
+                        *first = Some(i);
+                    }
+                }
+            }
+
+            assert_eq!(first, [Some(left), Some(right)], "{rate}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Sample rate must be non-zero")]
+    fn room_zero_sample_rate() {
+        Room::with_sample_rate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sample rate must be non-zero")]
+    fn reverb_zero_sample_rate() {
+        Reverb::with_sample_rate(0);
+    }
+
+    #[test]
+    fn reverb_freeze() {
+        let mut reverb = Reverb::new();
+        let input = || Frame::<Ch32, 2>::new(Ch32::new(0.5), Ch32::new(0.5));
+        let rms = |reverb: &mut Reverb, input: fn() -> Frame<Ch32, 2>| {
+            let sum: f32 = (0..48_000)
+                .map(|_| reverb.step(input()).channels()[0].to_f32())
+                .map(|x| x * x)
+                .sum();
+
+            libm::sqrtf(sum / 48_000.0)
+        };
+
+        // Excite the reverb, then freeze it
+        let mut white = crate::noise::White::new();
+
+        for _ in 0..4_800 {
+            let noise = white.step();
+
+            reverb.step(Frame::<Ch32, 2>::new(noise, noise));
+        }
+        reverb.set_freeze(true);
+
+        // Frozen reverb neither decays nor takes input
+        let first = rms(&mut reverb, Frame::default);
+        let second = rms(&mut reverb, input);
+
+        assert!(first > 0.01);
+        assert!((second - first).abs() < first * 0.1, "{first} {second}");
+
+        // Unfrozen reverb decays
+        reverb.set_freeze(false);
+        rms(&mut reverb, Frame::default);
+        assert!(rms(&mut reverb, Frame::default) < first * 0.01);
+    }
+
+    const ADSR_PRESS: [Segment; 2] =
+        [Segment::linear(0.01, 1.0), Segment::linear(0.1, 0.5)];
+    const ADSR_RELEASE: [Segment; 1] = [Segment::linear(0.1, 0.0)];
+
+    /// Run an envelope with a gate, returning every level
+    fn levels(envelope: &mut Envelope<'_>, gate: &[(f32, usize)]) -> Vec<f32> {
+        gate.iter()
+            .flat_map(|(gate, len)| core::iter::repeat(*gate).take(*len))
+            .map(|gate| envelope.step(gate.into()).to_f32())
+            .collect()
+    }
+
+    #[test]
+    fn envelope_adsr() {
+        for rate in RATES {
+            // Samples per 10 ms
+            let cs = rate as usize / 100;
+            let mut adsr = Envelope::with_sample_rate(
+                &ADSR_PRESS,
+                &ADSR_RELEASE,
+                Trigger::Retrigger,
+                rate,
+            );
+            let levels =
+                levels(&mut adsr, &[(0.0, 10), (1.0, 50 * cs), (0.0, 20 * cs)]);
+            let (idle, levels) = levels.split_at(10);
+            let (press, release) = levels.split_at(50 * cs);
+            let attack = press.iter().position(|x| *x == 1.0).unwrap();
+            let decay = press.iter().position(|x| *x == 0.5).unwrap() - attack;
+            let release_len = release.iter().position(|x| *x == 0.0).unwrap();
+
+            assert!(idle.iter().all(|x| *x == 0.0));
+            assert!(attack.abs_diff(cs) <= 1, "{rate}: {attack}");
+            assert!(decay.abs_diff(10 * cs) <= 1, "{rate}: {decay}");
+            assert!(press[attack + decay..].iter().all(|x| *x == 0.5));
+            assert!(
+                release_len.abs_diff(10 * cs) <= 1,
+                "{rate}: {release_len}"
+            );
+            assert!(release[release_len..].iter().all(|x| *x == 0.0));
+        }
+    }
+
+    #[test]
+    fn envelope_curves() {
+        let half = |segment: Segment| {
+            let press = [segment];
+            let mut envelope = Envelope::new(&press, &[], Trigger::Retrigger);
+
+            levels(&mut envelope, &[(1.0, 2_401)])[2_400]
+        };
+
+        let close = |segment, expected: f32| {
+            let level = half(segment);
+
+            assert!((level - expected).abs() < 0.001, "{level} {expected}");
+        };
+
+        close(Segment::linear(0.1, 1.0), 0.5);
+        close(Segment::bezier(0.1, 1.0, 0.0), 0.5);
+        close(Segment::bezier(0.1, 1.0, 1.0), 0.75);
+        close(Segment::bezier(0.1, 1.0, -1.0), 0.25);
+        close(Segment::exponential(0.1, 1.0), 0.924_234);
+    }
+
+    #[test]
+    fn envelope_trigger() {
+        let ms = 48;
+        let gate = [(1.0, 200 * ms), (0.0, 20 * ms), (1.0, 5 * ms)];
+        let mut retrigger =
+            Envelope::new(&ADSR_PRESS, &ADSR_RELEASE, Trigger::Retrigger);
+        let mut legato =
+            Envelope::new(&ADSR_PRESS, &ADSR_RELEASE, Trigger::Legato);
+        let retrigger = levels(&mut retrigger, &gate);
+        let legato = levels(&mut legato, &gate);
+        let repress = 220 * ms;
+
+        // Both start from the released level, without jumping
+        assert_eq!(retrigger[..repress], legato[..repress]);
+        assert!((retrigger[repress] - retrigger[repress - 1]).abs() < 0.01);
+        assert!((legato[repress] - legato[repress - 1]).abs() < 0.01);
+        // Retrigger attacks again, legato goes back to the sustain level
+        assert!(retrigger[repress + 1] > retrigger[repress]);
+        assert!(retrigger.last().unwrap() > &0.5);
+        assert!(legato[repress..].iter().all(|x| *x <= 0.5));
+        assert!(legato[repress + 1] > legato[repress]);
+    }
+
+    #[test]
+    fn envelope_instant() {
+        for time in [0.0, -0.0, -1.0, f32::NAN] {
+            let press = [Segment::linear(time, 1.0), Segment::linear(0.1, 0.5)];
+            let release = [Segment::bezier(time, 0.25, 1.0)];
+            let mut envelope =
+                Envelope::new(&press, &release, Trigger::Retrigger);
+            let levels = levels(&mut envelope, &[(1.0, 4_800), (0.0, 4_800)]);
+
+            // Jumps to the level, then carries on from it
+            assert_eq!(levels[0], 1.0, "{time}");
+            assert!((levels[2_400] - 0.75).abs() < 0.001, "{time}");
+            assert!(levels[4_800..].iter().all(|x| *x == 0.25), "{time}");
+        }
+    }
+}
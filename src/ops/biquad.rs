@@ -0,0 +1,190 @@
+use core::f32::consts::TAU;
+
+use fon::chan::{Ch32, Channel};
+
+/// Lowest allowed Q, to keep the filter from dividing by zero
+const MIN_Q: f32 = 0.001;
+
+/// Highest allowed cutoff, as a fraction of the sample rate
+const MAX_CUTOFF: f32 = 0.499;
+
+/// Frequency response of a [`Biquad`] filter.
+///
+/// Gains are in decibels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterType {
+    /// Reduce frequencies above the cutoff.
+    Lowpass,
+    /// Reduce frequencies below the cutoff.
+    Highpass,
+    /// Reduce frequencies outside of a band around the cutoff (0 dB peak).
+    Bandpass,
+    /// Reduce frequencies within a band around the cutoff.
+    Notch,
+    /// Boost or cut frequencies within a band around the cutoff.
+    Peaking {
+        /// Gain at the cutoff frequency (decibels).
+        gain: f32,
+    },
+    /// Boost or cut frequencies below the cutoff.
+    LowShelf {
+        /// Gain below the cutoff frequency (decibels).
+        gain: f32,
+    },
+    /// Boost or cut frequencies above the cutoff.
+    HighShelf {
+        /// Gain above the cutoff frequency (decibels).
+        gain: f32,
+    },
+}
+
+impl FilterType {
+    /// Calculate normalized filter coefficients `[b0, b1, b2, a1, a2]` from
+    /// the "Audio EQ Cookbook" by Robert Bristow-Johnson.
+    ///
+    /// - `cutoff`: Cutoff (or center) frequency (hertz)
+    /// - `q`: Quality factor (resonance)
+    /// - `period`: Seconds per sample
+    pub(crate) fn coefficients(
+        self,
+        cutoff: f32,
+        q: f32,
+        period: f32,
+    ) -> [f32; 5] {
+        let w0 = TAU * (cutoff * period).clamp(0.0, MAX_CUTOFF);
+        let (sin, cos) = (libm::sinf(w0), libm::cosf(w0));
+        let alpha = sin / (2.0 * q.max(MIN_Q));
+        let amp = |gain: f32| libm::powf(10.0, gain / 40.0);
+        let [b0, b1, b2, a0, a1, a2] = match self {
+            Self::Lowpass => {
+                let b = (1.0 - cos) * 0.5;
+
+                [b, 2.0 * b, b, 1.0 + alpha, -2.0 * cos, 1.0 - alpha]
+            }
+            Self::Highpass => {
+                let b = (1.0 + cos) * 0.5;
+
+                [b, -2.0 * b, b, 1.0 + alpha, -2.0 * cos, 1.0 - alpha]
+            }
+            Self::Bandpass => {
+                [alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha]
+            }
+            Self::Notch => {
+                [1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha]
+            }
+            Self::Peaking { gain } => {
+                let a = amp(gain);
+
+                [
+                    1.0 + alpha * a,
+                    -2.0 * cos,
+                    1.0 - alpha * a,
+                    1.0 + alpha / a,
+                    -2.0 * cos,
+                    1.0 - alpha / a,
+                ]
+            }
+            Self::LowShelf { gain } => {
+                let a = amp(gain);
+                let sq = 2.0 * libm::sqrtf(a) * alpha;
+
+                [
+                    a * ((a + 1.0) - (a - 1.0) * cos + sq),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                    a * ((a + 1.0) - (a - 1.0) * cos - sq),
+                    (a + 1.0) + (a - 1.0) * cos + sq,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                    (a + 1.0) + (a - 1.0) * cos - sq,
+                ]
+            }
+            Self::HighShelf { gain } => {
+                let a = amp(gain);
+                let sq = 2.0 * libm::sqrtf(a) * alpha;
+
+                [
+                    a * ((a + 1.0) + (a - 1.0) * cos + sq),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                    a * ((a + 1.0) + (a - 1.0) * cos - sq),
+                    (a + 1.0) - (a - 1.0) * cos + sq,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                    (a + 1.0) - (a - 1.0) * cos - sq,
+                ]
+            }
+        };
+        let a0 = a0.recip();
+
+        [b0 * a0, b1 * a0, b2 * a0, a1 * a0, a2 * a0]
+    }
+}
+
+/// Run one sample through a biquad (transposed direct form II), updating the
+/// two states.
+#[inline(always)]
+pub(crate) fn biquad(coeffs: &[f32; 5], state: &mut [f32; 2], x: f32) -> f32 {
+    let [b0, b1, b2, a1, a2] = *coeffs;
+    let y = b0 * x + state[0];
+
+    state[0] = b1 * x - a1 * y + state[1];
+    state[1] = b2 * x - a2 * y;
+    y
+}
+
+/// Biquad filter.
+///
+/// A second order IIR filter, with any of the responses from the RBJ "Audio EQ
+/// Cookbook" (see [`FilterType`]).  Cutoff and Q can change every sample.
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    /// Frequency response
+    filter: FilterType,
+    /// Seconds per sample
+    period: f32,
+    /// Filter states
+    state: [f32; 2],
+    /// Cutoff and Q the coefficients were calculated for
+    params: [f32; 2],
+    /// Normalized coefficients
+    coeffs: [f32; 5],
+}
+
+impl Biquad {
+    /// Create a new biquad filter for 48 kHz audio.
+    #[inline(always)]
+    pub fn new(filter: FilterType) -> Self {
+        Self::with_sample_rate(filter, 48_000)
+    }
+
+    /// Create a new biquad filter for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(filter: FilterType, sample_rate: u32) -> Self {
+        Self {
+            filter,
+            period: super::period(sample_rate),
+            state: [0.0; 2],
+            params: [f32::NAN; 2],
+            coeffs: [0.0; 5],
+        }
+    }
+
+    /// Change the sample rate (in Hz) this filter processes audio at.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.period = super::period(sample_rate);
+        self.params = [f32::NAN; 2];
+    }
+
+    /// Get next sample processed through the filter.
+    ///
+    /// - `input`: The signal being filtered
+    /// - `cutoff`: Cutoff (or center) frequency (hertz)
+    /// - `q`: Quality factor (0.707 for a flat passband)
+    #[inline(always)]
+    pub fn step(&mut self, input: Ch32, cutoff: f32, q: f32) -> Ch32 {
+        if self.params != [cutoff, q] {
+            self.params = [cutoff, q];
+            self.coeffs = self.filter.coefficients(cutoff, q, self.period);
+        }
+
+        biquad(&self.coeffs, &mut self.state, input.to_f32()).into()
+    }
+}
@@ -0,0 +1,283 @@
+use fon::chan::{Ch32, Channel};
+
+use crate::tree::osc::bezier;
+
+/// How quickly an exponential segment approaches its level (a time constant
+/// of ⅕ the segment's length)
+const EXP_RATE: f32 = 5.0;
+
+/// Stage of an idle envelope
+const IDLE: u32 = u32::MAX;
+
+/// Shape of an envelope [`Segment`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    /// Constant rate of change.
+    Linear,
+    /// Fast at first, then slowing down (like a charging capacitor).
+    Exponential,
+    /// Bend of a [`tree::osc::Bezier`](crate::tree::osc::Bezier) wave (-1 to
+    /// 1; 0 is linear, positive is fast at first).
+    Bezier(f32),
+}
+
+impl Curve {
+    /// Get the fraction of the way to the level at a fraction of the time
+    #[inline(always)]
+    fn shape(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::Exponential => {
+                (1.0 - libm::expf(-EXP_RATE * t))
+                    / (1.0 - libm::expf(-EXP_RATE))
+            }
+            Self::Bezier(curve) => bezier(t, curve),
+        }
+    }
+}
+
+/// One segment of an [`Envelope`], moving from the current level to a new
+/// level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    /// How long it takes to reach the level (seconds, jumping straight to the
+    /// level if not positive).
+    pub time: f32,
+    /// Level at the end of the segment.
+    pub level: f32,
+    /// Shape of the transition.
+    pub curve: Curve,
+}
+
+impl Segment {
+    /// Create a segment with a [`Curve::Linear`] shape.
+    pub const fn linear(time: f32, level: f32) -> Self {
+        Self {
+            time,
+            level,
+            curve: Curve::Linear,
+        }
+    }
+
+    /// Create a segment with a [`Curve::Exponential`] shape.
+    pub const fn exponential(time: f32, level: f32) -> Self {
+        Self {
+            time,
+            level,
+            curve: Curve::Exponential,
+        }
+    }
+
+    /// Create a segment with a [`Curve::Bezier`] shape.
+    pub const fn bezier(time: f32, level: f32, curve: f32) -> Self {
+        Self {
+            time,
+            level,
+            curve: Curve::Bezier(curve),
+        }
+    }
+}
+
+/// What happens when the gate of an [`Envelope`] opens again before the
+/// envelope finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// Restart from the first press segment.
+    Retrigger,
+    /// Continue with the last press segment (towards the sustain level).
+    Legato,
+}
+
+/// Playback state of an envelope
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EnvelopeState {
+    /// Index into the press segments followed by the release segments
+    stage: u32,
+    /// Whether the gate was open
+    gate: bool,
+    /// Seconds into the current segment
+    elapsed: f32,
+    /// Level at the start of the current segment
+    start: f32,
+    /// Current level
+    level: f32,
+}
+
+impl Default for EnvelopeState {
+    fn default() -> Self {
+        Self {
+            stage: IDLE,
+            gate: false,
+            elapsed: 0.0,
+            start: 0.0,
+            level: 0.0,
+        }
+    }
+}
+
+impl EnvelopeState {
+    /// Number of 32-bit states for [`EnvelopeState::load()`] and
+    /// [`EnvelopeState::store()`]
+    pub(crate) const STATE_LEN: usize = 5;
+
+    /// Load from 32-bit states (all zeros is an idle envelope)
+    pub(crate) fn load(state: &[u32]) -> Self {
+        Self {
+            stage: state[0].wrapping_sub(1),
+            gate: state[1] != 0,
+            elapsed: f32::from_bits(state[2]),
+            start: f32::from_bits(state[3]),
+            level: f32::from_bits(state[4]),
+        }
+    }
+
+    /// Store into 32-bit states
+    pub(crate) fn store(&self, state: &mut [u32]) {
+        state[..Self::STATE_LEN].copy_from_slice(&[
+            self.stage.wrapping_add(1),
+            self.gate.into(),
+            self.elapsed.to_bits(),
+            self.start.to_bits(),
+            self.level.to_bits(),
+        ]);
+    }
+
+    /// Get the next level of the envelope
+    pub(crate) fn step(
+        &mut self,
+        press: &[Segment],
+        release: &[Segment],
+        trigger: Trigger,
+        gate: f32,
+        period: f32,
+    ) -> f32 {
+        let open = gate > 0.0;
+        let pressed = press.len() as u32;
+        let total = pressed + release.len() as u32;
+
+        if open != self.gate {
+            self.stage = match (open, trigger) {
+                (false, _) => pressed,
+                (true, Trigger::Legato) if self.stage < total => {
+                    pressed.saturating_sub(1)
+                }
+                (true, _) => 0,
+            };
+            self.gate = open;
+            self.start = self.level;
+            self.elapsed = 0.0;
+        }
+
+        loop {
+            let segment = match self.stage {
+                stage if stage < pressed => press[stage as usize],
+                // Sustain
+                _ if open => return self.level,
+                stage if stage < total => release[(stage - pressed) as usize],
+                // Finished
+                _ => return self.level,
+            };
+            let t = self.elapsed / segment.time;
+
+            // Segments without a positive time jump straight to their level
+            if !(segment.time > 0.0) || t >= 1.0 {
+                self.stage += 1;
+                self.start = segment.level;
+                self.level = segment.level;
+                self.elapsed = 0.0;
+                continue;
+            }
+
+            self.level = self.start
+                + (segment.level - self.start) * segment.curve.shape(t);
+            self.elapsed += period;
+            return self.level;
+        }
+    }
+}
+
+/// Envelope generator.
+///
+/// While the gate is open (positive), plays the press segments, then holds
+/// the last level (sustain).  Once the gate closes, plays the release segments.
+/// Every segment starts from the current level, so a new gate never clicks.
+///
+/// An ADSR envelope has attack and decay press segments, and a release
+/// segment:
+///
+/// ```rust
+/// use twang::ops::{Envelope, Segment, Trigger};
+///
+/// const PRESS: [Segment; 2] = [
+///     Segment::linear(0.01, 1.0),
+///     Segment::exponential(0.1, 0.6),
+/// ];
+/// const RELEASE: [Segment; 1] = [Segment::exponential(0.3, 0.0)];
+///
+/// let mut adsr = Envelope::new(&PRESS, &RELEASE, Trigger::Retrigger);
+/// let level = adsr.step(1.0.into());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Envelope<'a> {
+    /// Segments played while the gate is open
+    press: &'a [Segment],
+    /// Segments played after the gate closes
+    release: &'a [Segment],
+    /// Behavior when the gate reopens
+    trigger: Trigger,
+    /// Seconds per sample
+    period: f32,
+    /// Playback state
+    state: EnvelopeState,
+}
+
+impl<'a> Envelope<'a> {
+    /// Create a new envelope generator for 48 kHz audio.
+    #[inline(always)]
+    pub fn new(
+        press: &'a [Segment],
+        release: &'a [Segment],
+        trigger: Trigger,
+    ) -> Self {
+        Self::with_sample_rate(press, release, trigger, 48_000)
+    }
+
+    /// Create a new envelope generator for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(
+        press: &'a [Segment],
+        release: &'a [Segment],
+        trigger: Trigger,
+        sample_rate: u32,
+    ) -> Self {
+        Self {
+            press,
+            release,
+            trigger,
+            period: super::period(sample_rate),
+            state: EnvelopeState::default(),
+        }
+    }
+
+    /// Change the sample rate (in Hz) this envelope generates audio for.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.period = super::period(sample_rate);
+    }
+
+    /// Get the next level of the envelope.
+    ///
+    /// - `gate`: Open while positive
+    #[inline(always)]
+    pub fn step(&mut self, gate: Ch32) -> Ch32 {
+        self.state
+            .step(
+                self.press,
+                self.release,
+                self.trigger,
+                gate.to_f32(),
+                self.period,
+            )
+            .into()
+    }
+}
@@ -11,21 +11,46 @@ use fon::chan::{Ch32, Channel};
 /// - `hold`: How long the signal is held before release once below threshold
 ///   (seconds).
 /// - `release`: How long it takes for gate to fully close (seconds).
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy)]
 pub struct Gate {
     /// The level of the gate (1.0 is fully closed, 0.0 is fully open).
     level: f32,
     /// Non-Infinity if gate is closing.
     hold: f32,
+    /// Seconds per sample.
+    period: f32,
+}
+
+impl Default for Gate {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_sample_rate(48_000)
+    }
 }
 
 impl Gate {
-    /// Create a new gate.
+    /// Create a new gate for 48 kHz audio.
     #[inline(always)]
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Create a new gate for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self {
+            level: 0.0,
+            hold: 0.0,
+            period: super::period(sample_rate),
+        }
+    }
+
+    /// Change the sample rate (in Hz) this gate processes audio at.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.period = super::period(sample_rate);
+    }
+
     /// Get next sample processed through the noise gate.
     #[inline(always)]
     pub fn step(&mut self, gate: &GateParams) -> Ch32 {
@@ -35,7 +60,7 @@ impl Gate {
             self.level = if gate.attack == 0.0 {
                 0.0
             } else {
-                (self.level - (1.0 / 48_000.0) / gate.attack).max(0.0)
+                (self.level - self.period / gate.attack).max(0.0)
             };
             // Reset hold value now that open.
             self.hold = f32::INFINITY;
@@ -52,13 +77,13 @@ impl Gate {
                 self.level = if gate.release == 0.0 {
                     1.0
                 } else {
-                    (self.level + (1.0 / 48_000.0) / gate.release).min(1.0)
+                    (self.level + self.period / gate.release).min(1.0)
                 };
             }
         }
         // Adjust hold time.
         if self.hold != f32::INFINITY {
-            self.hold = (self.hold - (1.0 / 48_000.0)).max(0.0);
+            self.hold = (self.hold - self.period).max(0.0);
         }
         // Calculate attenuation level.
         let level = 1.0 - gate.range.to_f32() * self.level;
@@ -0,0 +1,112 @@
+use fon::chan::{Ch32, Channel};
+
+use super::{svf::prewarp, FilterOutput};
+
+/// Run one sample through a ladder filter, updating the four states and
+/// returning the lowpass, bandpass and highpass outputs.
+#[inline(always)]
+pub(crate) fn ladder(
+    state: &mut [f32; 4],
+    x: f32,
+    cutoff: f32,
+    resonance: f32,
+    period: f32,
+) -> [f32; 3] {
+    let g = prewarp(cutoff, period);
+    let gain = g / (1.0 + g);
+    let k = 4.0 * resonance.max(0.0);
+    // Output of the last stage without input (zero-delay feedback)
+    let feedback = state.iter().fold(0.0, |sum, s| sum * gain + s / (1.0 + g));
+    let u =
+        libm::tanhf((x - k * feedback) / (1.0 + k * gain * gain * gain * gain));
+    let mut y = [0.0; 4];
+    let mut input = u;
+
+    for (s, y) in state.iter_mut().zip(y.iter_mut()) {
+        let v = (input - *s) * gain;
+
+        *y = v + *s;
+        *s = *y + v;
+        input = *y;
+    }
+
+    let [y1, y2, y3, y4] = y;
+
+    [
+        y4,
+        4.0 * (y2 - 2.0 * y3 + y4),
+        u - 4.0 * y1 + 6.0 * y2 - 4.0 * y3 + y4,
+    ]
+}
+
+/// Ladder filter.
+///
+/// A resonant four pole (24 dB/octave) filter, modeled after the Moog
+/// transistor ladder with a saturating input stage.  It uses zero-delay
+/// feedback, so it stays stable when the cutoff is swept at audio rate.
+/// Resonance ranges from 0 to 1, and the filter self-oscillates above 1 (the
+/// input stage limits the volume of the oscillation).
+#[derive(Debug, Clone, Copy)]
+pub struct Ladder {
+    /// Seconds per sample
+    period: f32,
+    /// Stage states
+    state: [f32; 4],
+}
+
+impl Default for Ladder {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ladder {
+    /// Create a new ladder filter for 48 kHz audio.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::with_sample_rate(48_000)
+    }
+
+    /// Create a new ladder filter for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self {
+            period: super::period(sample_rate),
+            state: [0.0; 4],
+        }
+    }
+
+    /// Change the sample rate (in Hz) this filter processes audio at.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.period = super::period(sample_rate);
+    }
+
+    /// Get next sample processed through the filter.
+    ///
+    /// - `input`: The signal being filtered
+    /// - `cutoff`: Cutoff frequency (hertz)
+    /// - `resonance`: Boost at the cutoff (0 to 1, self-oscillating above 1)
+    #[inline(always)]
+    pub fn step(
+        &mut self,
+        input: Ch32,
+        cutoff: f32,
+        resonance: f32,
+    ) -> FilterOutput {
+        let [lowpass, bandpass, highpass] = ladder(
+            &mut self.state,
+            input.to_f32(),
+            cutoff,
+            resonance,
+            self.period,
+        );
+
+        FilterOutput {
+            lowpass: lowpass.into(),
+            bandpass: bandpass.into(),
+            highpass: highpass.into(),
+        }
+    }
+}
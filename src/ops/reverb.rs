@@ -0,0 +1,268 @@
+use alloc::{vec, vec::Vec};
+
+use fon::{
+    chan::{Ch32, Channel},
+    Frame,
+};
+
+use crate::math;
+
+/// Sample rate the tunings were chosen for
+const TUNING_RATE: u32 = 44_100;
+/// Comb filter delay lengths (samples at 44.1 kHz)
+const COMB_TUNING: [usize; 8] =
+    [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// Allpass filter delay lengths (samples at 44.1 kHz)
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+/// Extra delay of the right channel (samples at 44.1 kHz)
+const STEREO_SPREAD: usize = 23;
+/// Allpass filter feedback
+const ALLPASS_FEEDBACK: f32 = 0.5;
+/// Input gain
+const FIXED_GAIN: f32 = 0.015;
+// Scaling of the parameters into the ranges the filters use
+const SCALE_WET: f32 = 3.0;
+const SCALE_DRY: f32 = 2.0;
+const SCALE_DAMP: f32 = 0.4;
+const SCALE_ROOM: f32 = 0.28;
+const OFFSET_ROOM: f32 = 0.7;
+
+/// Flush denormal numbers to zero
+#[inline(always)]
+fn undenormalise(sample: f32) -> f32 {
+    if sample.to_bits() & 0x7f80_0000 == 0 {
+        0.0
+    } else {
+        sample
+    }
+}
+
+/// Scale a delay length from 44.1 kHz to `sample_rate`
+fn tuning(samples: usize, sample_rate: u32) -> usize {
+    let scaled = samples as u64 * u64::from(sample_rate);
+    let rate = u64::from(TUNING_RATE);
+
+    (((scaled + rate / 2) / rate) as usize).max(1)
+}
+
+/// Lowpass-feedback comb filter
+#[derive(Debug, Clone)]
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    store: f32,
+}
+
+impl Comb {
+    fn new(size: usize) -> Self {
+        Self {
+            buffer: vec![0.0; size],
+            index: 0,
+            store: 0.0,
+        }
+    }
+
+    #[inline(always)]
+    fn process(&mut self, input: f32, feedback: f32, damp: [f32; 2]) -> f32 {
+        let output = undenormalise(self.buffer[self.index]);
+
+        self.store = undenormalise(output * damp[1] + self.store * damp[0]);
+        self.buffer[self.index] = input + self.store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+/// Schroeder allpass filter
+#[derive(Debug, Clone)]
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(size: usize) -> Self {
+        Self {
+            buffer: vec![0.0; size],
+            index: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = undenormalise(self.buffer[self.index]);
+
+        self.buffer[self.index] = input + delayed * ALLPASS_FEEDBACK;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// Stereo reverb.
+///
+/// A port of Jezar's public domain Freeverb: eight parallel lowpass-feedback
+/// comb filters, followed by four allpass filters in series, per channel.  The
+/// filter lengths are scaled from their 44.1 kHz tunings to the sample rate.
+///
+/// - `room_size`: How long the reverb lasts (0 to 1)
+/// - `damping`: How quickly high frequencies die out (0 to 1)
+/// - `wet`: Volume of the reverb (0 to 1)
+/// - `dry`: Volume of the input (0 to 1)
+/// - `width`: Stereo separation of the reverb (0 is mono, 1 is full stereo)
+/// - `freeze`: Whether the reverb rings forever, ignoring new input
+#[derive(Debug, Clone)]
+pub struct Reverb {
+    combs: [Vec<Comb>; 2],
+    allpasses: [Vec<Allpass>; 2],
+    room_size: f32,
+    damping: f32,
+    wet: f32,
+    dry: f32,
+    width: f32,
+    freeze: bool,
+    /// Comb filter feedback
+    feedback: f32,
+    /// Comb filter damping (lowpass state and input gains)
+    damp: [f32; 2],
+    /// Input gain
+    gain: f32,
+    /// Gain of the reverb into the same and the opposite channel
+    wet_gains: [f32; 2],
+}
+
+impl Default for Reverb {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reverb {
+    /// Create a new reverb for 48 kHz audio.
+    pub fn new() -> Self {
+        Self::with_sample_rate(48_000)
+    }
+
+    /// Create a new reverb for audio at `sample_rate` Hz.
+    ///
+    /// Starts with a room size and damping of 0.5, a wet volume of ⅓, a dry
+    /// volume of 0 and a width of 1.
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        let sample_rate = math::sample_rate(sample_rate);
+        let channel = |spread: usize| {
+            let combs = COMB_TUNING
+                .iter()
+                .map(|len| Comb::new(tuning(len + spread, sample_rate)))
+                .collect();
+            let allpasses = ALLPASS_TUNING
+                .iter()
+                .map(|len| Allpass::new(tuning(len + spread, sample_rate)))
+                .collect();
+
+            (combs, allpasses)
+        };
+        let (left_combs, left_allpasses) = channel(0);
+        let (right_combs, right_allpasses) = channel(STEREO_SPREAD);
+        let mut reverb = Self {
+            combs: [left_combs, right_combs],
+            allpasses: [left_allpasses, right_allpasses],
+            room_size: 0.5,
+            damping: 0.5,
+            wet: 1.0 / SCALE_WET,
+            dry: 0.0,
+            width: 1.0,
+            freeze: false,
+            feedback: 0.0,
+            damp: [0.0; 2],
+            gain: 0.0,
+            wet_gains: [0.0; 2],
+        };
+
+        reverb.update();
+        reverb
+    }
+
+    /// Set how long the reverb lasts (0 to 1).
+    pub fn set_room_size(&mut self, room_size: f32) {
+        self.room_size = room_size;
+        self.update();
+    }
+
+    /// Set how quickly high frequencies die out (0 to 1).
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping;
+        self.update();
+    }
+
+    /// Set the volume of the reverb (0 to 1).
+    pub fn set_wet(&mut self, wet: f32) {
+        self.wet = wet;
+        self.update();
+    }
+
+    /// Set the volume of the input (0 to 1).
+    pub fn set_dry(&mut self, dry: f32) {
+        self.dry = dry;
+    }
+
+    /// Set the stereo separation of the reverb (0 is mono, 1 is full stereo).
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width;
+        self.update();
+    }
+
+    /// Set whether the reverb rings forever, ignoring new input.
+    pub fn set_freeze(&mut self, freeze: bool) {
+        self.freeze = freeze;
+        self.update();
+    }
+
+    /// Recalculate internal values after a parameter change
+    fn update(&mut self) {
+        let wet = self.wet * SCALE_WET;
+
+        self.wet_gains = [
+            wet * (self.width / 2.0 + 0.5),
+            wet * ((1.0 - self.width) / 2.0),
+        ];
+
+        let (feedback, damp, gain) = if self.freeze {
+            (1.0, 0.0, 0.0)
+        } else {
+            (
+                self.room_size * SCALE_ROOM + OFFSET_ROOM,
+                self.damping * SCALE_DAMP,
+                FIXED_GAIN,
+            )
+        };
+
+        self.feedback = feedback;
+        self.damp = [damp, 1.0 - damp];
+        self.gain = gain;
+    }
+
+    /// Get next stereo frame processed through the reverb.
+    pub fn step(&mut self, input: Frame<Ch32, 2>) -> Frame<Ch32, 2> {
+        let [left, right] = (*input.channels()).map(Channel::to_f32);
+        let mono = (left + right) * self.gain;
+        let (feedback, damp) = (self.feedback, self.damp);
+        let [out_l, out_r] = [0, 1].map(|channel| {
+            let mut out = 0.0;
+
+            for comb in &mut self.combs[channel] {
+                out += comb.process(mono, feedback, damp);
+            }
+            for allpass in &mut self.allpasses[channel] {
+                out = allpass.process(out);
+            }
+            out
+        });
+        let dry = self.dry * SCALE_DRY;
+        let [same, opposite] = self.wet_gains;
+
+        Frame::<Ch32, 2>::new(
+            (out_l * same + out_r * opposite + left * dry).into(),
+            (out_r * same + out_l * opposite + right * dry).into(),
+        )
+    }
+}
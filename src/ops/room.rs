@@ -1,29 +1,55 @@
 use alloc::collections::VecDeque;
 use fon::chan::{Ch32, Channel};
 
+use crate::math;
+
 /// Room effect.  Use to add reflections to the sound.  Reflections can create
 /// either echo (> 50 ms delay) or reverb (< 30 ms delay).
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Room {
     buffer: VecDeque<Ch32>,
+    /// Samples per second.
+    sample_rate: f32,
+}
+
+impl Default for Room {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Room {
-    /// Create a new Room Effect.
+    /// Create a new Room Effect for 48 kHz audio.
     #[inline(always)]
     pub fn new() -> Self {
+        Self::with_sample_rate(48_000)
+    }
+
+    /// Create a new Room Effect for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
         Room {
             buffer: VecDeque::new(),
+            sample_rate: math::sample_rate(sample_rate) as f32,
         }
     }
 
+    /// Change the sample rate (in Hz) this room processes audio at.
+    ///
+    /// Reflections that were already added keep their offset in samples.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = math::sample_rate(sample_rate) as f32;
+    }
+
     /// Add reflection to the room.
     /// - `signal`: the input signal
     /// - `seconds`: the number of seconds it takes for the reflection to occur
     /// - `attenuation`: the gain to multiply by after each reflection
     #[inline(always)]
     pub fn add(&mut self, signal: Ch32, seconds: f32, attenuation: f32) {
-        let offset = (48_000.0 * seconds) as usize;
+        let offset = (self.sample_rate * seconds) as usize;
         self.buffer.resize(offset + 1, Ch32::default());
         self.buffer[offset] += Ch32::new(signal.to_f32() * attenuation);
     }
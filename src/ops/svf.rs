@@ -0,0 +1,118 @@
+use core::f32::consts::PI;
+
+use fon::chan::{Ch32, Channel};
+
+/// Highest allowed cutoff, as a fraction of the sample rate
+const MAX_CUTOFF: f32 = 0.49;
+
+/// Simultaneous outputs of a multimode filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FilterOutput {
+    /// Frequencies above the cutoff reduced.
+    pub lowpass: Ch32,
+    /// Frequencies away from the cutoff reduced.
+    pub bandpass: Ch32,
+    /// Frequencies below the cutoff reduced.
+    pub highpass: Ch32,
+}
+
+/// Get the gain of a one-pole trapezoidal integrator with a cutoff frequency
+/// (prewarped, so the cutoff stays in tune at any sample rate).
+#[inline(always)]
+pub(crate) fn prewarp(cutoff: f32, period: f32) -> f32 {
+    libm::tanf(PI * (cutoff * period).clamp(0.0, MAX_CUTOFF))
+}
+
+/// Run one sample through a state-variable filter, updating the two states
+/// and returning the lowpass, bandpass and highpass outputs.
+#[inline(always)]
+pub(crate) fn svf(
+    state: &mut [f32; 2],
+    x: f32,
+    cutoff: f32,
+    resonance: f32,
+    period: f32,
+) -> [f32; 3] {
+    let g = prewarp(cutoff, period);
+    let k = 2.0 - 2.0 * resonance.clamp(0.0, 1.0);
+    let a1 = (1.0 + g * (g + k)).recip();
+    let a2 = g * a1;
+    let a3 = g * a2;
+    let v3 = x - state[1];
+    let v1 = a1 * state[0] + a2 * v3;
+    let v2 = state[1] + a2 * state[0] + a3 * v3;
+
+    state[0] = 2.0 * v1 - state[0];
+    state[1] = 2.0 * v2 - state[1];
+    [v2, v1, x - k * v1 - v2]
+}
+
+/// State-variable filter.
+///
+/// A resonant two pole (12 dB/octave) filter, using topology-preserving
+/// transform (TPT), so it stays stable when the cutoff is swept at audio rate.
+/// Resonance ranges from 0 to 1, and the filter self-oscillates at 1.
+#[derive(Debug, Clone, Copy)]
+pub struct Svf {
+    /// Seconds per sample
+    period: f32,
+    /// Integrator states
+    state: [f32; 2],
+}
+
+impl Default for Svf {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Svf {
+    /// Create a new state-variable filter for 48 kHz audio.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::with_sample_rate(48_000)
+    }
+
+    /// Create a new state-variable filter for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self {
+            period: super::period(sample_rate),
+            state: [0.0; 2],
+        }
+    }
+
+    /// Change the sample rate (in Hz) this filter processes audio at.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.period = super::period(sample_rate);
+    }
+
+    /// Get next sample processed through the filter.
+    ///
+    /// - `input`: The signal being filtered
+    /// - `cutoff`: Cutoff frequency (hertz)
+    /// - `resonance`: Boost at the cutoff (0 to 1, self-oscillating at 1)
+    #[inline(always)]
+    pub fn step(
+        &mut self,
+        input: Ch32,
+        cutoff: f32,
+        resonance: f32,
+    ) -> FilterOutput {
+        let [lowpass, bandpass, highpass] = svf(
+            &mut self.state,
+            input.to_f32(),
+            cutoff,
+            resonance,
+            self.period,
+        );
+
+        FilterOutput {
+            lowpass: lowpass.into(),
+            bandpass: bandpass.into(),
+            highpass: highpass.into(),
+        }
+    }
+}
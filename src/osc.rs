@@ -1,18 +1,409 @@
 //! A collection of basic oscillators (wave generators).
+//!
+//! # Panics
+//! Oscillators panic if they are given a `sample_rate` of 0.
 
 #![allow(warnings)]
 
+mod bl_pulse;
+mod bl_sawtooth;
+mod bl_triangle;
+pub(crate) mod blep;
+mod fft;
 mod pulse;
 mod sawtooth;
 mod sine;
 mod triangle;
+mod wavetable;
+mod waypoints;
 
 use core::f32::consts::TAU;
 
+use crate::math::period;
+
+pub use bl_pulse::BlPulse;
+pub use bl_sawtooth::BlSawtooth;
+pub use bl_triangle::BlTriangle;
 pub use pulse::Pulse;
 pub use sawtooth::Sawtooth;
 pub use sine::Sine;
 pub use triangle::Triangle;
+pub use wavetable::{TableOsc, Wavetable};
+pub use waypoints::{Transition, Waypoint, Waypoints};
+
+pub(crate) use waypoints::{sorted, waypoints};
 
-// Seconds per sample.
+// Seconds per sample at the default sample rate of 48 kHz.
 const SAMPLE_PERIOD: f32 = 1.0 / 48_000.0;
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use fon::chan::Ch32;
+
+    use super::*;
+
+    const RATES: [u32; 4] = [22_050, 44_100, 48_000, 96_000];
+
+    /// Count the cycles generated over one second of audio
+    fn cycles(sample_rate: u32, mut step: impl FnMut() -> Ch32) -> usize {
+        let mut last = f32::from(step());
+        let mut count = 0;
+
+        for _ in 1..sample_rate {
+            let sample = f32::from(step());
+
+            if last < 0.0 && sample >= 0.0 {
+                count += 1;
+            }
+            last = sample;
+        }
+
+        count
+    }
+
+    #[test]
+    fn pitch() {
+        for rate in RATES {
+            let mut sine = Sine::with_sample_rate(rate);
+            let mut saw = Sawtooth::with_sample_rate(rate);
+            let mut tri = Triangle::with_sample_rate(rate);
+            let mut pulse = Pulse::with_sample_rate(rate);
+            let counts = [
+                cycles(rate, || sine.step(440.0)),
+                cycles(rate, || saw.step(440.0)),
+                cycles(rate, || tri.step(440.0)),
+                cycles(rate, || pulse.step(440.0, Ch32::new(0.0))),
+            ];
+
+            for count in counts {
+                assert!((439..=441).contains(&count), "{rate}: {count}");
+            }
+        }
+    }
+
+    #[test]
+    fn set_sample_rate() {
+        let mut sine = Sine::new();
+
+        sine.set_sample_rate(96_000);
+        assert_eq!(cycles(96_000, || sine.step(1000.0)), 1000);
+
+        // Default matches 48 kHz
+        let mut a = Sine::new();
+        let mut b = Sine::with_sample_rate(48_000);
+
+        for _ in 0..48_000 {
+            assert_eq!(a.step(440.0), b.step(440.0));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Sample rate must be non-zero")]
+    fn zero_sample_rate() {
+        Sine::new().set_sample_rate(0);
+    }
+
+    /// Get the fraction of one second of audio's energy that isn't at a
+    /// harmonic of `hz` (the aliasing, for a whole number `hz`)
+    fn aliasing(
+        sample_rate: u32,
+        hz: u32,
+        mut step: impl FnMut() -> Ch32,
+    ) -> f64 {
+        let samples: Vec<f64> =
+            (0..sample_rate).map(|_| f32::from(step()).into()).collect();
+        let total: f64 = samples.iter().map(|x| x * x).sum();
+        let len = samples.len() as f64;
+        let harmonics: f64 = (0..=sample_rate / 2 / hz)
+            .map(|k| {
+                let w = TAU as f64 * f64::from(k * hz) / len;
+                let (mut re, mut im) = (0.0, 0.0);
+
+                for (n, x) in samples.iter().enumerate() {
+                    re += x * (w * n as f64).cos();
+                    im -= x * (w * n as f64).sin();
+                }
+
+                let energy = (re * re + im * im) / len;
+
+                if k == 0 {
+                    energy
+                } else {
+                    energy * 2.0
+                }
+            })
+            .sum();
+
+        (total - harmonics) / total
+    }
+
+    #[test]
+    fn band_limited() {
+        const HZ: u32 = 2_637;
+
+        for rate in [44_100, 48_000] {
+            let hz = HZ as f32;
+            let duty = Ch32::new(-0.3);
+            let mut saw = Sawtooth::with_sample_rate(rate);
+            let mut tri = Triangle::with_sample_rate(rate);
+            let mut pulse = Pulse::with_sample_rate(rate);
+            let mut bl_saw = BlSawtooth::with_sample_rate(rate);
+            let mut bl_tri = BlTriangle::with_sample_rate(rate);
+            let mut bl_pulse = BlPulse::with_sample_rate(rate);
+            let pairs = [
+                (
+                    aliasing(rate, HZ, || saw.step(hz)),
+                    aliasing(rate, HZ, || bl_saw.step(hz)),
+                ),
+                (
+                    aliasing(rate, HZ, || tri.step(hz)),
+                    aliasing(rate, HZ, || bl_tri.step(hz)),
+                ),
+                (
+                    aliasing(rate, HZ, || pulse.step(hz, duty)),
+                    aliasing(rate, HZ, || bl_pulse.step(hz, duty)),
+                ),
+            ];
+
+            // At least 10 dB less aliasing
+            for (naive, band_limited) in pairs {
+                assert!(band_limited * 10.0 < naive, "{rate}: {pairs:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn band_limited_pitch() {
+        for rate in RATES {
+            let mut saw = BlSawtooth::with_sample_rate(rate);
+            let mut tri = BlTriangle::with_sample_rate(rate);
+            let mut pulse = BlPulse::with_sample_rate(rate);
+            let counts = [
+                cycles(rate, || saw.step(440.0)),
+                cycles(rate, || tri.step(440.0)),
+                cycles(rate, || pulse.step(440.0, Ch32::new(0.0))),
+            ];
+
+            for count in counts {
+                assert!((439..=441).contains(&count), "{rate}: {count}");
+            }
+        }
+    }
+
+    /// One cycle of a falling sawtooth
+    fn saw_table(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 1.0 - 2.0 * i as f32 / len as f32)
+            .collect()
+    }
+
+    #[test]
+    fn wavetable_band_limited() {
+        const HZ: u32 = 2_637;
+
+        let table = saw_table(2048);
+        let wavetable = Wavetable::new(&[&table]);
+
+        for rate in [44_100, 48_000] {
+            let hz = HZ as f32;
+            let position = Ch32::new(0.0);
+            let mut saw = Sawtooth::with_sample_rate(rate);
+            let mut osc = TableOsc::with_sample_rate(&wavetable, rate);
+            let naive = aliasing(rate, HZ, || saw.step(hz));
+            let band_limited = aliasing(rate, HZ, || osc.step(hz, position));
+
+            assert!(band_limited * 100.0 < naive, "{naive} {band_limited}");
+        }
+    }
+
+    #[test]
+    fn wavetable_pitch() {
+        let table = saw_table(600);
+        let wavetable = Wavetable::new(&[&table]);
+
+        for rate in RATES {
+            let mut osc = TableOsc::with_sample_rate(&wavetable, rate);
+            let count = cycles(rate, || osc.step(440.0, Ch32::new(0.0)));
+
+            assert!((439..=441).contains(&count), "{rate}: {count}");
+        }
+
+        // At a low pitch, all harmonics are kept
+        let mut osc = TableOsc::new(&wavetable);
+        let mut saw = Sawtooth::new();
+
+        for _ in 0..48_000 {
+            let a = f32::from(osc.step(80.0, Ch32::new(0.0)));
+            let b = f32::from(saw.step(80.0));
+
+            assert!((a - b).abs() < 0.2 || b.abs() > 0.9, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn wavetable_negative_hz() {
+        let table = saw_table(600);
+        let wavetable = Wavetable::new(&[&table]);
+
+        for rate in RATES {
+            let mut osc = TableOsc::with_sample_rate(&wavetable, rate);
+            let mut peak = 0.0f32;
+            let count = cycles(rate, || {
+                let sample = osc.step(-440.0, Ch32::new(0.0));
+
+                peak = peak.max(f32::from(sample).abs());
+                sample
+            });
+
+            // Plays backwards, within the table's range
+            assert!((439..=441).contains(&count), "{rate}: {count}");
+            assert!(peak < 1.2, "{rate}: {peak}");
+        }
+    }
+
+    #[test]
+    fn wavetable_mipmaps() {
+        use core::f32::consts::TAU;
+
+        // Fundamental, and a harmonic that's removed from lower mip-maps
+        let table: Vec<f32> = (0..1_000)
+            .map(|i| {
+                let x = TAU * i as f32 / 1_000.0;
+
+                libm::sinf(x) + 0.5 * libm::sinf(300.0 * x)
+            })
+            .collect();
+        let wavetable = Wavetable::new(&[&table]);
+
+        for i in 0..1_000 {
+            let phase = i as f32 / 1_000.0;
+            let full = wavetable.sample(phase, 1.0 / 48_000.0, 0.0);
+            let filtered = wavetable.sample(phase, 1.0 / 400.0, 0.0);
+            let sine = libm::sinf(TAU * phase);
+
+            assert!((full - table[i]).abs() < 0.0001, "{full} {}", table[i]);
+            assert!((filtered - sine).abs() < 0.0001, "{filtered} {sine}");
+        }
+    }
+
+    #[test]
+    fn wavetable_morph() {
+        let square: Vec<f32> =
+            (0..64).map(|i| if i < 32 { 1.0 } else { -1.0 }).collect();
+        let saw = saw_table(128);
+        let square_only = Wavetable::new(&[&square]);
+        let saw_only = Wavetable::new(&[&saw]);
+        let morph = Wavetable::new(&[&square, &saw]);
+
+        for i in 0..1_000 {
+            let phase = i as f32 / 1_000.0;
+            let dt = 100.0 / 48_000.0;
+            let a = square_only.sample(phase, dt, 0.0);
+            let b = saw_only.sample(phase, dt, 0.0);
+
+            assert_eq!(morph.sample(phase, dt, -1.0), a);
+            assert_eq!(morph.sample(phase, dt, 0.0), a);
+            assert_eq!(morph.sample(phase, dt, 1.0), b);
+            assert_eq!(morph.sample(phase, dt, 2.0), b);
+            assert!(
+                (morph.sample(phase, dt, 0.25) - (a * 0.75 + b * 0.25)).abs()
+                    < 0.0001
+            );
+        }
+    }
+
+    const STEPS: [Waypoint; 4] = [
+        Waypoint::new(0.0, 1.0),
+        Waypoint::new(0.25, 0.5),
+        Waypoint::new(0.5, -0.5),
+        Waypoint::new(0.75, -1.0),
+    ];
+
+    #[test]
+    fn waypoints_transitions() {
+        let jump = |t| waypoints(&STEPS, Transition::Jump, t);
+        let linear = |t| waypoints(&STEPS, Transition::Linear, t);
+
+        assert_eq!(jump(0.0), 1.0);
+        assert_eq!(jump(0.3), 0.5);
+        assert_eq!(jump(0.74), -0.5);
+        assert_eq!(jump(0.99), -1.0);
+        assert_eq!(linear(0.125), 0.75);
+        assert_eq!(linear(0.5), -0.5);
+        // Wraps around to the first waypoint
+        assert_eq!(linear(0.875), 0.0);
+
+        for i in 0..75 {
+            let t = i as f32 / 100.0;
+            let bezier =
+                |curve| waypoints(&STEPS, Transition::Bezier(curve), t);
+
+            assert!((bezier(0.0) - linear(t)).abs() < 0.0001);
+            // Fast at first, and every waypoint until 0.75 is lower
+            assert!(bezier(1.0) <= linear(t) + 0.0001);
+        }
+
+        // First waypoint after the start of the cycle
+        let offset = [Waypoint::new(0.25, 1.0), Waypoint::new(0.75, -1.0)];
+
+        assert_eq!(waypoints(&offset, Transition::Linear, 0.0), 0.0);
+        assert_eq!(waypoints(&offset, Transition::Jump, 0.0), -1.0);
+        assert_eq!(waypoints(&[], Transition::Linear, 0.5), 0.0);
+        assert_eq!(waypoints(&offset[..1], Transition::Linear, 0.5), 1.0);
+    }
+
+    #[test]
+    fn waypoints_pitch() {
+        for rate in RATES {
+            let mut jump =
+                Waypoints::with_sample_rate(&STEPS, Transition::Jump, rate);
+            let mut linear =
+                Waypoints::with_sample_rate(&STEPS, Transition::Linear, rate);
+            let counts = [
+                cycles(rate, || jump.step(440.0)),
+                cycles(rate, || linear.step(440.0)),
+            ];
+
+            for count in counts {
+                assert!((439..=441).contains(&count), "{rate}: {count}");
+            }
+        }
+    }
+
+    #[test]
+    fn waypoints_negative_hz() {
+        let points = [Waypoint::new(0.0, 1.0), Waypoint::new(0.5, -1.0)];
+
+        // Phase before the start of the cycle wraps around
+        assert_eq!(waypoints(&points, Transition::Linear, -0.25), 0.0);
+        assert_eq!(waypoints(&points, Transition::Jump, -0.25), -1.0);
+
+        for transition in [Transition::Linear, Transition::Bezier(0.5)] {
+            for rate in RATES {
+                let mut osc =
+                    Waypoints::with_sample_rate(&points, transition, rate);
+                let mut peak = 0.0f32;
+                let count = cycles(rate, || {
+                    let sample = osc.step(-440.0);
+
+                    peak = peak.max(f32::from(sample).abs());
+                    sample
+                });
+
+                assert!((439..=441).contains(&count), "{rate}: {count}");
+                assert!(peak <= 1.0, "{rate}: {peak}");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Waypoints must be sorted by phase")]
+    fn waypoints_unsorted() {
+        let points = [Waypoint::new(0.5, 1.0), Waypoint::new(0.0, -1.0)];
+
+        Waypoints::new(&points, Transition::Linear);
+    }
+}
@@ -0,0 +1,50 @@
+use fon::chan::Ch32;
+
+/// Band-limited pulse wave generator.
+///
+/// Like [`Pulse`](super::Pulse), but with both edges smoothed out by a
+/// PolyBLEP to greatly reduce aliasing at high pitches.  The duty cycle is
+/// controlled by a side-chain channel: 0.0 produces a square wave, -1.0
+/// produces a constant signal of -1.0, and +1.0 produces a constant signal of
+/// +1.0.
+#[derive(Clone, Copy, Debug)]
+pub struct BlPulse(f32, f32);
+
+impl Default for BlPulse {
+    #[inline(always)]
+    fn default() -> Self {
+        Self(0.0, super::SAMPLE_PERIOD)
+    }
+}
+
+impl BlPulse {
+    /// Create a new band-limited pulse wave generator for 48 kHz audio.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new band-limited pulse wave generator for audio at
+    /// `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self(0.0, super::period(sample_rate))
+    }
+
+    /// Change the sample rate (in Hz) this oscillator generates audio for,
+    /// keeping the current phase.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.1 = super::period(sample_rate);
+    }
+
+    /// Get the next sample from this oscillator.
+    #[inline(always)]
+    pub fn step(&mut self, hz: f32, duty: Ch32) -> Ch32 {
+        let dt = self.1 * hz;
+        let width = (f32::from(duty).clamp(-1.0, 1.0) + 1.0) * 0.5;
+        let out = super::blep::pulse(self.0, dt, width);
+        self.0 = (self.0 + dt) % 1.0;
+        out.into()
+    }
+}
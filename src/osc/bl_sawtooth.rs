@@ -0,0 +1,46 @@
+use fon::chan::Ch32;
+
+/// Band-limited sawtooth wave generator.
+///
+/// Like [`Sawtooth`](super::Sawtooth), but with the jump smoothed out by a
+/// PolyBLEP to greatly reduce aliasing at high pitches.
+#[derive(Clone, Copy, Debug)]
+pub struct BlSawtooth(f32, f32);
+
+impl Default for BlSawtooth {
+    #[inline(always)]
+    fn default() -> Self {
+        Self(0.0, super::SAMPLE_PERIOD)
+    }
+}
+
+impl BlSawtooth {
+    /// Create a new band-limited sawtooth wave generator for 48 kHz audio.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new band-limited sawtooth wave generator for audio at
+    /// `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self(0.0, super::period(sample_rate))
+    }
+
+    /// Change the sample rate (in Hz) this oscillator generates audio for,
+    /// keeping the current phase.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.1 = super::period(sample_rate);
+    }
+
+    /// Get the next sample from this oscillator.
+    #[inline(always)]
+    pub fn step(&mut self, hz: f32) -> Ch32 {
+        let dt = self.1 * hz;
+        let out = super::blep::sawtooth(self.0, dt);
+        self.0 = (self.0 + dt) % 1.0;
+        out.into()
+    }
+}
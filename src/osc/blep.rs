@@ -0,0 +1,60 @@
+//! Polynomial band-limited step (PolyBLEP) and ramp (PolyBLAMP) residuals.
+//!
+//! Phases are in cycles (0 to 1), and `dt` is the phase increment per sample.
+
+/// Correction for a step of +2 at phase 0, spread over the samples next to it
+#[inline(always)]
+pub(crate) fn blep(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let t = t / dt;
+
+        t + t - t * t - 1.0
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt;
+
+        t * t + t + t + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// Correction for a change in slope of +2 per sample at phase 0, spread over
+/// the samples next to it
+#[inline(always)]
+pub(crate) fn blamp(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let t = t / dt - 1.0;
+
+        t * t * t * (-1.0 / 3.0)
+    } else if t > 1.0 - dt {
+        let t = (t - 1.0) / dt + 1.0;
+
+        t * t * t * (1.0 / 3.0)
+    } else {
+        0.0
+    }
+}
+
+/// Band-limited sawtooth (falling from 1 to -1) at phase `t`
+#[inline(always)]
+pub(crate) fn sawtooth(t: f32, dt: f32) -> f32 {
+    1.0 - 2.0 * t + blep(t, dt)
+}
+
+/// Band-limited pulse at phase `t`, positive for the first `width` of a cycle
+#[inline(always)]
+pub(crate) fn pulse(t: f32, dt: f32, width: f32) -> f32 {
+    let falling = (t - width + 1.0) % 1.0;
+    let naive = if t < width { 1.0 } else { -1.0 };
+
+    naive + blep(t, dt) - blep(falling, dt)
+}
+
+/// Band-limited triangle (1 at phase 0, -1 at phase ½) at phase `t`
+#[inline(always)]
+pub(crate) fn triangle(t: f32, dt: f32) -> f32 {
+    let rising = (t + 0.5) % 1.0;
+    let naive = 4.0 * (t - 0.5).abs() - 1.0;
+
+    naive + 4.0 * dt * (blamp(rising, dt) - blamp(t, dt))
+}
@@ -0,0 +1,207 @@
+//! Discrete Fourier transforms of any length in O(n log n) time.
+//!
+//! Power of 2 lengths use a radix-2 FFT.  Other lengths are zero-padded to a
+//! power of 2 with Bluestein's algorithm, which gives the same result as the
+//! naive transform.
+
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+/// Complex number
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct Complex {
+    pub(crate) re: f64,
+    pub(crate) im: f64,
+}
+
+impl Complex {
+    pub(crate) const fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// Unit complex number at an angle (radians)
+    fn polar(angle: f64) -> Self {
+        Self::new(libm::cos(angle), libm::sin(angle))
+    }
+
+    fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+
+    fn scale(self, amount: f64) -> Self {
+        Self::new(self.re * amount, self.im * amount)
+    }
+}
+
+/// Twiddle factors for a power of 2 length FFT
+fn twiddles(len: usize) -> Vec<Complex> {
+    (0..len / 2)
+        .map(|k| Complex::polar(-2.0 * PI * k as f64 / len as f64))
+        .collect()
+}
+
+/// In-place forward FFT of a power of 2 length buffer, with its twiddles
+fn radix2(buf: &mut [Complex], twiddles: &[Complex]) {
+    let len = buf.len();
+
+    if len <= 1 {
+        return;
+    }
+
+    // Bit-reversal permutation
+    let bits = len.trailing_zeros();
+
+    for i in 0..len {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    // Twiddles of the largest butterflies are reused by smaller ones
+    let mut size = 2;
+
+    while size <= len {
+        let stride = len / size;
+
+        for start in (0..len).step_by(size) {
+            for k in 0..size / 2 {
+                let a = buf[start + k];
+                let b = buf[start + k + size / 2].mul(twiddles[k * stride]);
+
+                buf[start + k] = a.add(b);
+                buf[start + k + size / 2] = a.sub(b);
+            }
+        }
+
+        size *= 2;
+    }
+}
+
+/// Discrete Fourier transform of a fixed length
+#[derive(Debug)]
+pub(crate) struct Dft {
+    len: usize,
+    /// Twiddle factors of the (padded) power of 2 FFT
+    twiddles: Vec<Complex>,
+    /// Bluestein chirp (empty for power of 2 lengths)
+    chirp: Vec<Complex>,
+    /// FFT of the conjugate chirp, zero-padded and wrapped around
+    kernel: Vec<Complex>,
+}
+
+impl Dft {
+    /// Plan a transform of `len` samples
+    pub(crate) fn new(len: usize) -> Self {
+        if len.is_power_of_two() || len == 0 {
+            return Self {
+                len,
+                twiddles: twiddles(len),
+                chirp: Vec::new(),
+                kernel: Vec::new(),
+            };
+        }
+
+        // Bluestein's algorithm: the transform as a convolution with a
+        // chirp, done with zero-padded power of 2 FFTs
+        let padded = (2 * len - 1).next_power_of_two();
+        let twiddles = twiddles(padded);
+        // Squares taken modulo 2 * len, to keep the angles precise
+        let chirp: Vec<Complex> = (0..len as u64)
+            .map(|n| {
+                let square = (n * n) % (2 * len as u64);
+
+                Complex::polar(-PI * square as f64 / len as f64)
+            })
+            .collect();
+        let mut kernel = vec![Complex::default(); padded];
+
+        for (n, chirp) in chirp.iter().enumerate() {
+            kernel[n] = chirp.conj();
+            if n != 0 {
+                kernel[padded - n] = chirp.conj();
+            }
+        }
+
+        radix2(&mut kernel, &twiddles);
+
+        Self {
+            len,
+            twiddles,
+            chirp,
+            kernel,
+        }
+    }
+
+    /// Forward transform
+    ///
+    /// # Panics
+    ///
+    /// If the input isn't the planned length.
+    pub(crate) fn forward(&self, input: &[Complex]) -> Vec<Complex> {
+        assert_eq!(input.len(), self.len);
+
+        if self.chirp.is_empty() {
+            let mut buf = input.to_vec();
+
+            radix2(&mut buf, &self.twiddles);
+            return buf;
+        }
+
+        let mut buf = vec![Complex::default(); self.kernel.len()];
+
+        for (buf, (sample, chirp)) in
+            buf.iter_mut().zip(input.iter().zip(&self.chirp))
+        {
+            *buf = sample.mul(*chirp);
+        }
+
+        radix2(&mut buf, &self.twiddles);
+
+        // Inverse FFT of the product, by conjugating before and after
+        for (buf, kernel) in buf.iter_mut().zip(&self.kernel) {
+            *buf = buf.mul(*kernel).conj();
+        }
+
+        radix2(&mut buf, &self.twiddles);
+
+        let scale = (buf.len() as f64).recip();
+
+        self.chirp
+            .iter()
+            .zip(buf)
+            .map(|(chirp, sample)| chirp.mul(sample.conj()).scale(scale))
+            .collect()
+    }
+
+    /// Inverse transform
+    ///
+    /// # Panics
+    ///
+    /// If the input isn't the planned length.
+    pub(crate) fn inverse(&self, input: &[Complex]) -> Vec<Complex> {
+        let scale = (self.len.max(1) as f64).recip();
+        let conj: Vec<Complex> = input.iter().map(|bin| bin.conj()).collect();
+
+        self.forward(&conj)
+            .into_iter()
+            .map(|sample| sample.conj().scale(scale))
+            .collect()
+    }
+}
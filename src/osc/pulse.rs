@@ -8,16 +8,36 @@ use fon::chan::Ch32;
 /// The duty cycle of the pulse wave is controlled by a side-chain channel.
 /// A value of 0.0 produces a square wave.  -1.0 produces a constant signal of
 /// -1.0, and +1.0 produces a constant signal of +1.0.
-#[derive(Default, Clone, Copy, Debug)]
-pub struct Pulse(f32);
+#[derive(Clone, Copy, Debug)]
+pub struct Pulse(f32, f32);
+
+impl Default for Pulse {
+    #[inline(always)]
+    fn default() -> Self {
+        Self(0.0, super::SAMPLE_PERIOD)
+    }
+}
 
 impl Pulse {
-    /// Create a new sine wave generator.
+    /// Create a new pulse wave generator for 48 kHz audio.
     #[inline(always)]
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Create a new pulse wave generator for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self(0.0, super::period(sample_rate))
+    }
+
+    /// Change the sample rate (in Hz) this oscillator generates audio for,
+    /// keeping the current phase.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.1 = super::period(sample_rate);
+    }
+
     /// Get the next sample from the oscillator without progressing oscillator.
     #[inline(always)]
     pub fn peek(&mut self, duty: Ch32) -> Ch32 {
@@ -28,7 +48,7 @@ impl Pulse {
     #[inline(always)]
     pub fn step(&mut self, hz: f32, duty: Ch32) -> Ch32 {
         let out = self.peek(duty);
-        self.0 = (self.0 + 2.0 * super::SAMPLE_PERIOD * hz) % 2.0;
+        self.0 = (self.0 + 2.0 * self.1 * hz) % 2.0;
         out
     }
 
@@ -41,9 +61,9 @@ impl Pulse {
         } else {
             f32::from(shift)
         };
-        self.0 = (original + 2.0 * (super::SAMPLE_PERIOD * hz + shift)) % 2.0;
+        self.0 = (original + 2.0 * (self.1 * hz + shift)) % 2.0;
         let out = self.peek(duty);
-        self.0 = (original + 2.0 * super::SAMPLE_PERIOD * hz) % 2.0;
+        self.0 = (original + 2.0 * self.1 * hz) % 2.0;
         out
     }
 
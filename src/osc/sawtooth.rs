@@ -1,16 +1,36 @@
 use fon::chan::Ch32;
 
 /// Sawtooth wave generator.
-#[derive(Default, Clone, Copy, Debug)]
-pub struct Sawtooth(f32);
+#[derive(Clone, Copy, Debug)]
+pub struct Sawtooth(f32, f32);
+
+impl Default for Sawtooth {
+    #[inline(always)]
+    fn default() -> Self {
+        Self(0.0, super::SAMPLE_PERIOD)
+    }
+}
 
 impl Sawtooth {
-    /// Create a new sine wave generator.
+    /// Create a new sawtooth wave generator for 48 kHz audio.
     #[inline(always)]
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Create a new sawtooth wave generator for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self(0.0, super::period(sample_rate))
+    }
+
+    /// Change the sample rate (in Hz) this oscillator generates audio for,
+    /// keeping the current phase.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.1 = super::period(sample_rate);
+    }
+
     /// Get the next sample from the oscillator without progressing oscillator.
     #[inline(always)]
     pub fn peek(&mut self) -> Ch32 {
@@ -21,7 +41,7 @@ impl Sawtooth {
     #[inline(always)]
     pub fn step(&mut self, hz: f32) -> Ch32 {
         let out = self.peek();
-        self.0 = (self.0 + 2.0 * super::SAMPLE_PERIOD * hz) % 2.0;
+        self.0 = (self.0 + 2.0 * self.1 * hz) % 2.0;
         out
     }
 
@@ -34,9 +54,9 @@ impl Sawtooth {
         } else {
             f32::from(shift)
         };
-        self.0 = (original + 2.0 * (super::SAMPLE_PERIOD * hz + shift)) % 2.0;
+        self.0 = (original + 2.0 * (self.1 * hz + shift)) % 2.0;
         let out = self.peek();
-        self.0 = (original + 2.0 * super::SAMPLE_PERIOD * hz) % 2.0;
+        self.0 = (original + 2.0 * self.1 * hz) % 2.0;
         out
     }
 
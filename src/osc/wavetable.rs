@@ -0,0 +1,196 @@
+use alloc::vec::Vec;
+
+use fon::chan::Ch32;
+
+use crate::math::Libm;
+
+use super::fft::{Complex, Dft};
+
+/// One table, band-limited to fewer harmonics at each level
+#[derive(Clone, Debug)]
+struct Mipmaps {
+    /// Samples per cycle
+    len: usize,
+    /// Harmonics kept at the first level
+    harmonics: usize,
+    /// Tables (with the first sample repeated at the end), one per octave
+    levels: Vec<Vec<f32>>,
+}
+
+impl Mipmaps {
+    fn new(table: &[f32]) -> Self {
+        let len = table.len().max(1);
+        let harmonics = (len - 1) / 2;
+        let mut samples: Vec<Complex> = table
+            .iter()
+            .map(|sample| Complex::new(f64::from(*sample), 0.0))
+            .collect();
+
+        samples.resize(len, Complex::default());
+
+        // Fourier series of the table
+        let dft = Dft::new(len);
+        let spectrum = dft.forward(&samples);
+        let mut levels = Vec::new();
+        let mut kept = harmonics;
+
+        loop {
+            // Remove harmonics above `kept`, at both positive and negative
+            // frequencies to keep the table real
+            let bins: Vec<Complex> = spectrum
+                .iter()
+                .enumerate()
+                .map(|(k, bin)| {
+                    if k.min(len - k) <= kept {
+                        *bin
+                    } else {
+                        Complex::default()
+                    }
+                })
+                .collect();
+            let mut level: Vec<f32> = dft
+                .inverse(&bins)
+                .iter()
+                .map(|sample| sample.re as f32)
+                .collect();
+
+            level.push(level[0]);
+            levels.push(level);
+
+            if kept <= 1 {
+                break;
+            }
+
+            kept /= 2;
+        }
+
+        Self {
+            len,
+            harmonics,
+            levels,
+        }
+    }
+
+    /// Look up a sample, without harmonics above the Nyquist frequency
+    ///
+    /// - `phase`: Position in the cycle (0 to 1)
+    /// - `dt`: Cycles per sample
+    #[inline(always)]
+    fn sample(&self, phase: f32, dt: f32) -> f32 {
+        let nyquist = 0.5 / dt.abs();
+        let mut kept = self.harmonics;
+        let mut level = 0;
+
+        while kept as f32 >= nyquist && level + 1 < self.levels.len() {
+            kept /= 2;
+            level += 1;
+        }
+
+        let table = &self.levels[level];
+        let index = phase.rem_euclid(1.0) * self.len as f32;
+        let i = (index as usize).min(self.len - 1);
+        let fract = (index - i as f32).clamp(0.0, 1.0);
+
+        table[i] + (table[i + 1] - table[i]) * fract
+    }
+}
+
+/// Mip-mapped wavetables for [`TableOsc`].
+///
+/// Each table is one cycle of a waveform, of any length.  The tables are split
+/// into per-octave mip-maps, each band-limited to half the harmonics of the
+/// one before, so that no harmonics above the Nyquist frequency are played at
+/// any pitch.  With more than one table, a position signal morphs between
+/// them.
+///
+/// ```rust
+/// use twang::osc::{TableOsc, Wavetable};
+///
+/// // Morph from a square wave to a sawtooth
+/// let square: Vec<f32> =
+///     (0..256).map(|i| if i < 128 { 1.0 } else { -1.0 }).collect();
+/// let saw: Vec<f32> = (0..256).map(|i| 1.0 - i as f32 / 128.0).collect();
+/// let wavetable = Wavetable::new(&[&square, &saw]);
+/// let mut osc = TableOsc::new(&wavetable);
+/// let sample = osc.step(440.0, 0.25.into());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Wavetable(Vec<Mipmaps>);
+
+impl Wavetable {
+    /// Create mip-mapped wavetables, morphed through in order.
+    ///
+    /// An empty table is silent.
+    pub fn new(tables: &[&[f32]]) -> Self {
+        let mut mipmaps: Vec<Mipmaps> =
+            tables.iter().map(|table| Mipmaps::new(table)).collect();
+
+        if mipmaps.is_empty() {
+            mipmaps.push(Mipmaps::new(&[]));
+        }
+
+        Self(mipmaps)
+    }
+
+    /// Look up a band-limited sample
+    ///
+    /// - `phase`: Position in the cycle (0 to 1)
+    /// - `dt`: Cycles per sample
+    /// - `position`: Morph position (0 is the first table, 1 is the last)
+    #[inline(always)]
+    pub(crate) fn sample(&self, phase: f32, dt: f32, position: f32) -> f32 {
+        let last = self.0.len() - 1;
+        let index = position.clamp(0.0, 1.0) * last as f32;
+        let i = (index as usize).min(last);
+        let a = self.0[i].sample(phase, dt);
+
+        if i == last {
+            return a;
+        }
+
+        let b = self.0[i + 1].sample(phase, dt);
+
+        a + (b - a) * (index - i as f32)
+    }
+}
+
+/// Wavetable oscillator.
+///
+/// Plays [`Wavetable`]s with linear interpolation, picking the mip-map for
+/// the pitch, and morphing between tables by a side-chain channel (0 is the
+/// first table, 1 is the last).
+#[derive(Clone, Copy, Debug)]
+pub struct TableOsc<'a>(&'a Wavetable, f32, f32);
+
+impl<'a> TableOsc<'a> {
+    /// Create a new wavetable oscillator for 48 kHz audio.
+    #[inline(always)]
+    pub fn new(wavetable: &'a Wavetable) -> Self {
+        Self(wavetable, 0.0, super::SAMPLE_PERIOD)
+    }
+
+    /// Create a new wavetable oscillator for audio at `sample_rate` Hz.
+    #[inline(always)]
+    pub fn with_sample_rate(
+        wavetable: &'a Wavetable,
+        sample_rate: u32,
+    ) -> Self {
+        Self(wavetable, 0.0, super::period(sample_rate))
+    }
+
+    /// Change the sample rate (in Hz) this oscillator generates audio for,
+    /// keeping the current phase.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.2 = super::period(sample_rate);
+    }
+
+    /// Get the next sample from this oscillator.
+    #[inline(always)]
+    pub fn step(&mut self, hz: f32, position: Ch32) -> Ch32 {
+        let dt = self.2 * hz;
+        let out = self.0.sample(self.1, dt, f32::from(position));
+        self.1 = (self.1 + dt).rem_euclid(1.0);
+        out.into()
+    }
+}
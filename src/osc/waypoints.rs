@@ -0,0 +1,135 @@
+use fon::chan::Ch32;
+
+use crate::{math::Libm, tree::osc::bezier};
+
+/// A point in the cycle of a [`Waypoints`] oscillator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Waypoint {
+    /// Position in the cycle (0 to 1).
+    pub phase: f32,
+    /// Sample value at this position.
+    pub value: f32,
+}
+
+impl Waypoint {
+    /// Create a new waypoint.
+    pub const fn new(phase: f32, value: f32) -> Self {
+        Self { phase, value }
+    }
+}
+
+/// How a [`Waypoints`] oscillator moves from one waypoint to the next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    /// Hold each value until the next waypoint (step-hold).
+    Jump,
+    /// Constant rate of change between waypoints.
+    Linear,
+    /// Bend of a [`tree::osc::Bezier`](crate::tree::osc::Bezier) wave (-1 to
+    /// 1; 0 is linear, positive is fast at first).
+    Bezier(f32),
+}
+
+/// Check that waypoints are sorted by phase
+pub(crate) fn sorted(points: &[Waypoint]) -> bool {
+    points.windows(2).all(|pair| pair[0].phase <= pair[1].phase)
+}
+
+/// Get the sample at phase `t` (in cycles, wrapped to 0 to 1) of a cycle
+/// through waypoints sorted by phase
+#[inline(always)]
+pub(crate) fn waypoints(
+    points: &[Waypoint],
+    transition: Transition,
+    t: f32,
+) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return 0.0;
+    };
+    let t = t.rem_euclid(1.0);
+    let index = points.iter().position(|point| point.phase > t);
+    let (prev, prev_phase) = match index {
+        Some(0) => (last, last.phase - 1.0),
+        Some(i) => (&points[i - 1], points[i - 1].phase),
+        None => (last, last.phase),
+    };
+    let (next, next_phase) = match index {
+        Some(i) => (&points[i], points[i].phase),
+        None => (first, first.phase + 1.0),
+    };
+    let span = next_phase - prev_phase;
+    let x = if span > 0.0 {
+        (t - prev_phase) / span
+    } else {
+        0.0
+    };
+    let shape = match transition {
+        Transition::Jump => return prev.value,
+        Transition::Linear => x,
+        Transition::Bezier(curve) => bezier(x, curve),
+    };
+
+    prev.value + (next.value - prev.value) * shape
+}
+
+/// Waypoint (arbitrary waveform) oscillator.
+///
+/// Cycles through waypoints (sorted by phase), either jumping from one value to
+/// the next, or interpolating between them.  Nothing is band-limited, so this
+/// aliases on purpose, as heard in video game music.
+///
+/// ```rust
+/// use twang::osc::{Transition, Waypoint, Waypoints};
+///
+/// // Chiptune-style 4-step wave
+/// const STEPS: [Waypoint; 4] = [
+///     Waypoint::new(0.0, 1.0),
+///     Waypoint::new(0.25, 0.5),
+///     Waypoint::new(0.5, -0.5),
+///     Waypoint::new(0.75, -1.0),
+/// ];
+///
+/// let mut osc = Waypoints::new(&STEPS, Transition::Jump);
+/// let sample = osc.step(440.0);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Waypoints<'a>(&'a [Waypoint], Transition, f32, f32);
+
+impl<'a> Waypoints<'a> {
+    /// Create a new waypoint oscillator for 48 kHz audio.
+    ///
+    /// `points` must be sorted by phase (checked in debug builds).
+    #[inline(always)]
+    pub fn new(points: &'a [Waypoint], transition: Transition) -> Self {
+        debug_assert!(sorted(points), "Waypoints must be sorted by phase");
+        Self(points, transition, 0.0, super::SAMPLE_PERIOD)
+    }
+
+    /// Create a new waypoint oscillator for audio at `sample_rate` Hz.
+    ///
+    /// `points` must be sorted by phase (checked in debug builds).
+    #[inline(always)]
+    pub fn with_sample_rate(
+        points: &'a [Waypoint],
+        transition: Transition,
+        sample_rate: u32,
+    ) -> Self {
+        debug_assert!(sorted(points), "Waypoints must be sorted by phase");
+        Self(points, transition, 0.0, super::period(sample_rate))
+    }
+
+    /// Change the sample rate (in Hz) this oscillator generates audio for,
+    /// keeping the current phase.
+    #[inline(always)]
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.3 = super::period(sample_rate);
+    }
+
+    /// Get the next sample from this oscillator.
+    #[inline(always)]
+    pub fn step(&mut self, hz: f32) -> Ch32 {
+        let out = waypoints(self.0, self.1, self.2);
+        self.2 = (self.2 + self.3 * hz).rem_euclid(1.0);
+        out.into()
+    }
+}
@@ -8,7 +8,7 @@ use fon::{Frame, Sink};
 /// A synthesizer stream.
 pub struct Synth<S, const CH: usize>(
     S,
-    Box<dyn FnMut(&mut S, Frame<Ch32, CH>) -> Frame<Ch32, CH>>,
+    Box<dyn FnMut(&mut S, Frame<Ch32, CH>, u32) -> Frame<Ch32, CH>>,
 );
 
 impl<S, const CH: usize> Debug for Synth<S, CH> {
@@ -19,9 +19,12 @@ impl<S, const CH: usize> Debug for Synth<S, CH> {
 
 impl<S, const CH: usize> Synth<S, CH> {
     /// Create a new synthesizer function.
+    ///
+    /// The function is called once per frame with the synthesizer state, a
+    /// silent frame, and the sample rate (in Hz) of the stream being rendered.
     pub fn new<F>(s: S, f: F) -> Self
     where
-        F: 'static + FnMut(&mut S, Frame<Ch32, CH>) -> Frame<Ch32, CH>,
+        F: 'static + FnMut(&mut S, Frame<Ch32, CH>, u32) -> Frame<Ch32, CH>,
     {
         Self(s, Box::new(f))
     }
@@ -44,7 +47,43 @@ impl<S, const CH: usize> Iterator for SynthIter<'_, S, CH> {
     type Item = Frame<Ch32, CH>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        assert_eq!(self.1, 48_000);
-        Some(self.0 .1(&mut self.0 .0, Default::default()))
+        Some(self.0 .1(&mut self.0 .0, Default::default(), self.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fon::{pos::Mono, Audio};
+
+    use super::*;
+    use crate::osc::Sine;
+
+    #[test]
+    fn sample_rates() {
+        for rate in [22_050, 44_100, 48_000, 96_000] {
+            let mut audio = Audio::<Ch32, 1>::with_silence(rate, rate as usize);
+            let mut synth = Synth::new(
+                (Sine::new(), 0),
+                |(sine, rate), frame: Frame<_, 1>, sample_rate| {
+                    if *rate != sample_rate {
+                        sine.set_sample_rate(sample_rate);
+                        *rate = sample_rate;
+                    }
+                    frame.pan(sine.step(440.0), 0.0)
+                },
+            );
+
+            synth.stream(audio.sink());
+
+            let crossings = audio
+                .iter()
+                .zip(audio.iter().skip(1))
+                .filter(|(a, b)| {
+                    f32::from(a[Mono]) < 0.0 && f32::from(b[Mono]) >= 0.0
+                })
+                .count();
+
+            assert!((439..=441).contains(&crossings), "{rate}: {crossings}");
+        }
     }
 }
@@ -97,4 +97,44 @@ impl Chunk {
             *sample = libm::fminf(libm::fmaxf(*sample, -1.0), 1.0)
         })
     }
+
+    #[inline(always)]
+    #[must_use]
+    pub(super) fn min(self, other: impl Borrow<Self>) -> Self {
+        (self, other.borrow()).for_each_sample(|(s, o)| *s = libm::fminf(*s, o))
+    }
+
+    #[inline(always)]
+    #[must_use]
+    pub(super) fn max(self, other: impl Borrow<Self>) -> Self {
+        (self, other.borrow()).for_each_sample(|(s, o)| *s = libm::fmaxf(*s, o))
+    }
+
+    #[inline(always)]
+    #[must_use]
+    pub(super) fn near(self, limit: impl Borrow<Self>) -> Self {
+        (self, limit.borrow()).for_each_sample(|(s, limit)| {
+            let limit = limit.abs();
+
+            *s = if *s < 0.0 {
+                libm::fmaxf(*s, -limit)
+            } else {
+                libm::fminf(*s, limit)
+            }
+        })
+    }
+
+    #[inline(always)]
+    #[must_use]
+    pub(super) fn far(self, limit: impl Borrow<Self>) -> Self {
+        (self, limit.borrow()).for_each_sample(|(s, limit)| {
+            let limit = limit.abs();
+
+            *s = if *s < 0.0 {
+                libm::fminf(*s, -limit)
+            } else {
+                libm::fmaxf(*s, limit)
+            }
+        })
+    }
 }
@@ -0,0 +1,62 @@
+use crate::{
+    ops::{EnvelopeState, Segment, Trigger},
+    tree::{consts, Chunk, Data, Wave},
+};
+
+/// Envelope generator
+///
+/// Takes gate (open while positive) as input, followed by the segments played
+/// while the gate is open, the segments played after it closes, and what
+/// happens when it reopens; see [`ops::Envelope`](crate::ops::Envelope)
+///
+/// ```rust
+/// use fon::{chan::Ch16, Audio};
+/// use twang::tree::{
+///     envelope::{Segment, Trigger},
+///     line::Line,
+///     Synth,
+/// };
+///
+/// const PRESS: &[Segment] =
+///     &[Segment::linear(0.01, 1.0), Segment::exponential(0.1, 0.6)];
+/// const RELEASE: &[Segment] = &[Segment::exponential(0.3, 0.0)];
+///
+/// // Gate opens for ½ second every second
+/// let waveform = const {
+///     Line(1.0)
+///         .osc()
+///         .envelope(PRESS, RELEASE, Trigger::Retrigger)
+/// };
+/// let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 48_000 * 5);
+/// let mut synth = Synth::new(waveform, []);
+///
+/// synth.stream(audio.sink());
+/// ```
+#[derive(Debug)]
+pub struct Envelope<I>(
+    pub I,
+    pub &'static [Segment],
+    pub &'static [Segment],
+    pub Trigger,
+);
+
+impl<I> Wave for Envelope<I>
+where
+    I: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + EnvelopeState::STATE_LEN;
+    const PARAMS: usize = I::PARAMS;
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let gate = data.input(&self.0, 0);
+        let period = data.chunk_step * consts::FRAC_32[1];
+        let state = &mut data.state[I::STATE_LEN..];
+        let mut envelope = EnvelopeState::load(state);
+        let chunk = gate.for_each_sample(|sample| {
+            *sample = envelope.step(self.1, self.2, self.3, *sample, period);
+        });
+
+        envelope.store(state);
+        chunk
+    }
+}
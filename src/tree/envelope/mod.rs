@@ -0,0 +1,42 @@
+//! Envelope generators
+
+#![allow(clippy::module_inception)]
+
+const_postfix_waveform!(Envelope<T>, T);
+
+mod envelope;
+
+pub use self::envelope::Envelope;
+pub use crate::ops::{Curve, Segment, Trigger};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ops,
+        tree::{line::Line, testing::render},
+    };
+
+    const PRESS: &[Segment] =
+        &[Segment::linear(0.01, 1.0), Segment::exponential(0.1, 0.6)];
+    const RELEASE: &[Segment] = &[Segment::bezier(0.2, 0.0, 0.5)];
+
+    #[test]
+    fn matches_ops() {
+        for trigger in [Trigger::Retrigger, Trigger::Legato] {
+            // Gate opens for ⅛ second, 4 times a second (before release ends)
+            let gate = Line(4.0).osc();
+            let mut envelope = ops::Envelope::new(PRESS, RELEASE, trigger);
+            let expected = render(&gate)
+                .into_iter()
+                .map(|x| f32::from(envelope.step(x.into())));
+
+            for (a, b) in render(gate.envelope(PRESS, RELEASE, trigger))
+                .into_iter()
+                .zip(expected)
+            {
+                assert!((a - b).abs() < 0.0001, "{a} != {b}");
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    ops::{self, FilterType},
+    tree::{consts, params, Chunk, Data, Wave},
+};
+
+/// Number of 32-bit states used by the filter itself
+const STATE_LEN: usize = 2;
+
+/// Biquad filter
+///
+/// Takes audio, cutoff (hertz) and Q as input; see [`ops::Biquad`]
+#[derive(Debug)]
+pub struct Biquad<I, J, K>(pub I, pub FilterType, pub J, pub K);
+
+impl<I, J, K> Wave for Biquad<I, J, K>
+where
+    I: Wave,
+    J: Wave,
+    K: Wave,
+{
+    const STATE_LEN: usize =
+        I::STATE_LEN + J::STATE_LEN + K::STATE_LEN + STATE_LEN;
+    const PARAMS: usize = params(params(I::PARAMS, J::PARAMS), K::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let cutoff = data.input(&self.2, I::STATE_LEN);
+        let q = data.input(&self.3, I::STATE_LEN + J::STATE_LEN);
+        let period = data.chunk_step * consts::FRAC_32[1];
+        let offset = I::STATE_LEN + J::STATE_LEN + K::STATE_LEN;
+        let state = &mut data.state[offset..][..STATE_LEN];
+        let mut filter = [f32::from_bits(state[0]), f32::from_bits(state[1])];
+        let mut last = [f32::NAN; 2];
+        let mut coeffs = [0.0; 5];
+        let mut i = 0;
+        let chunk = chunk.for_each_sample(|sample| {
+            let params = [cutoff.0[i], q.0[i]];
+
+            if params != last {
+                last = params;
+                coeffs = self.1.coefficients(params[0], params[1], period);
+            }
+
+            *sample = ops::biquad(&coeffs, &mut filter, *sample);
+            i += 1;
+        });
+
+        state[0] = filter[0].to_bits();
+        state[1] = filter[1].to_bits();
+        chunk
+    }
+}
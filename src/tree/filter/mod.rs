@@ -0,0 +1,192 @@
+//! Filters (frequency response shapers)
+
+const_postfix_waveform!(Biquad<T, U, V>, T, U, V);
+const_postfix_waveform!(Ladder<T, U, V>, T, U, V);
+const_postfix_waveform!(Svf<T, U, V>, T, U, V);
+
+mod biquad;
+mod ladder;
+mod svf;
+
+pub use self::{biquad::Biquad, ladder::Ladder, svf::Svf};
+pub use crate::ops::FilterType;
+
+/// Output of a multimode filter ([`Svf`] or [`Ladder`])
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Mode {
+    /// Frequencies above the cutoff reduced
+    Lowpass,
+    /// Frequencies away from the cutoff reduced
+    Bandpass,
+    /// Frequencies below the cutoff reduced
+    Highpass,
+    /// Mix of every output, each multiplied by its gain
+    ///
+    /// All outputs are read from the same filter state, so they stay in sync
+    /// while the cutoff is modulated (unlike separate filters, one per
+    /// output).
+    Mix {
+        /// Gain of the lowpass output
+        lowpass: f32,
+        /// Gain of the bandpass output
+        bandpass: f32,
+        /// Gain of the highpass output
+        highpass: f32,
+    },
+}
+
+impl Mode {
+    /// Get the output for this mode from the lowpass, bandpass and highpass
+    /// outputs
+    #[inline(always)]
+    fn output(self, [lowpass, bandpass, highpass]: [f32; 3]) -> f32 {
+        match self {
+            Self::Lowpass => lowpass,
+            Self::Bandpass => bandpass,
+            Self::Highpass => highpass,
+            Self::Mix {
+                lowpass: l,
+                bandpass: b,
+                highpass: h,
+            } => lowpass * l + bandpass * b + highpass * h,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use fon::{chan::Ch32, Audio};
+
+    use super::*;
+    use crate::{
+        ops,
+        tree::{
+            line::{Line, Param, Source},
+            testing::render,
+            Synth,
+        },
+    };
+
+    #[test]
+    fn matches_legacy_ops() {
+        let saw = Line(110.0).osc();
+        let filter = FilterType::Peaking { gain: -3.0 };
+        let mut biquad = ops::Biquad::new(filter);
+        let expected = render(&saw)
+            .into_iter()
+            .map(|x| f32::from(biquad.step(x.into(), 800.0, 2.0)));
+
+        for (a, b) in render(saw.biquad(filter, Line(800.0), Line(2.0)))
+            .into_iter()
+            .zip(expected)
+        {
+            assert!((a - b).abs() < 0.0001, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn multimode_matches_legacy_ops() {
+        let saw = Line(110.0).osc();
+        let modes = [Mode::Lowpass, Mode::Bandpass, Mode::Highpass];
+
+        for mode in modes {
+            let mut svf = ops::Svf::new();
+            let mut ladder = ops::Ladder::new();
+            let svf_wave = Svf(&saw, mode, Line(800.0), Line(0.5));
+            let ladder_wave = Ladder(&saw, mode, Line(800.0), Line(0.5));
+            let outputs = render(&saw).into_iter().map(|x| {
+                let svf = svf.step(x.into(), 800.0, 0.5);
+                let ladder = ladder.step(x.into(), 800.0, 0.5);
+                let select = |out: ops::FilterOutput| match mode {
+                    Mode::Lowpass => out.lowpass,
+                    Mode::Bandpass => out.bandpass,
+                    Mode::Highpass => out.highpass,
+                    Mode::Mix { .. } => unreachable!(),
+                };
+
+                (f32::from(select(svf)), f32::from(select(ladder)))
+            });
+
+            for ((a, b), (c, d)) in render(svf_wave)
+                .into_iter()
+                .zip(render(ladder_wave))
+                .zip(outputs)
+            {
+                assert!((a - c).abs() < 0.0001, "{mode:?}: {a} != {c}");
+                assert!((b - d).abs() < 0.0001, "{mode:?}: {b} != {d}");
+            }
+        }
+    }
+
+    #[test]
+    fn mix_shares_state() {
+        let saw = Line(110.0).osc();
+        // Cutoff swept at audio rate, between 200 Hz and 5 kHz
+        let sweep: Vec<f32> = (0..48_000)
+            .map(|i| 2_600.0 + 2_400.0 * (i as f32 * 0.05).sin())
+            .collect();
+        let cutoff = Audio::<Ch32, 1>::with_f32_buffer(48_000, sweep.clone());
+        let mix = Mode::Mix {
+            lowpass: 1.0,
+            bandpass: -0.5,
+            highpass: 0.25,
+        };
+        let mut svf = ops::Svf::new();
+        let mut ladder = ops::Ladder::new();
+        let expected =
+            render(&saw).into_iter().zip(&sweep).map(|(x, cutoff)| {
+                let mix = |out: ops::FilterOutput| {
+                    f32::from(out.lowpass) - f32::from(out.bandpass) * 0.5
+                        + f32::from(out.highpass) * 0.25
+                };
+
+                (
+                    mix(svf.step(x.into(), *cutoff, 0.9)),
+                    mix(ladder.step(x.into(), *cutoff, 0.9)),
+                )
+            });
+        let svf_wave = Svf(&saw, mix, Source(&cutoff), Line(0.9));
+        let ladder_wave = Ladder(&saw, mix, Source(&cutoff), Line(0.9));
+
+        for ((a, b), (c, d)) in render(svf_wave)
+            .into_iter()
+            .zip(render(ladder_wave))
+            .zip(expected)
+        {
+            assert!((a - c).abs() < 0.0001, "{a} != {c}");
+            assert!((b - d).abs() < 0.0001, "{b} != {d}");
+        }
+    }
+
+    #[test]
+    fn cutoff_sweep() {
+        const CUTOFF: Param<1> = Param::new(0);
+
+        let saw = Line(110.0).osc();
+        let mut audio = Audio::<Ch32, 1>::with_silence(48_000, 32);
+        let mut svf =
+            Synth::new(Svf(&saw, Mode::Bandpass, CUTOFF, Line(0.9)), [20.0]);
+        let mut ladder =
+            Synth::new(Ladder(&saw, Mode::Lowpass, CUTOFF, Line(0.9)), [20.0]);
+        let check = |samples: &[f32]| {
+            for sample in samples {
+                assert!(sample.is_finite() && sample.abs() < 16.0, "{sample}");
+            }
+        };
+
+        // Cutoff jumps between 20 Hz and 19 kHz every chunk
+        for i in 0..1_500 {
+            let cutoff = if i % 2 == 0 { 19_000.0 } else { 20.0 };
+
+            svf.params()[CUTOFF.index()] = cutoff;
+            ladder.params()[CUTOFF.index()] = cutoff;
+
+            svf.stream(audio.sink());
+            check(audio.as_f32_slice());
+            ladder.stream(audio.sink());
+            check(audio.as_f32_slice());
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    ops,
+    tree::{consts, params, Chunk, Data, Wave},
+};
+
+use super::Mode;
+
+/// Number of 32-bit states used by the filter itself
+const STATE_LEN: usize = 2;
+
+/// State-variable filter
+///
+/// Takes audio, cutoff (hertz) and resonance as input; see [`ops::Svf`]
+///
+/// Plays one output, or a mix of several from the same filter state with
+/// [`Mode::Mix`]
+///
+/// ```rust
+/// use fon::{chan::Ch16, Audio};
+/// use twang::tree::{
+///     filter::{Mode, Svf},
+///     line::Line,
+///     Synth,
+/// };
+///
+/// // Notch (lowpass and highpass, without the bandpass)
+/// let notch = Mode::Mix {
+///     lowpass: 1.0,
+///     bandpass: 0.0,
+///     highpass: 1.0,
+/// };
+/// let waveform = Svf(Line(110.0).osc(), notch, Line(800.0), Line(0.5));
+/// let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 48_000 * 5);
+/// let mut synth = Synth::new(waveform, []);
+///
+/// synth.stream(audio.sink());
+/// ```
+#[derive(Debug)]
+pub struct Svf<I, J, K>(pub I, pub Mode, pub J, pub K);
+
+impl<I, J, K> Wave for Svf<I, J, K>
+where
+    I: Wave,
+    J: Wave,
+    K: Wave,
+{
+    const STATE_LEN: usize =
+        I::STATE_LEN + J::STATE_LEN + K::STATE_LEN + STATE_LEN;
+    const PARAMS: usize = params(params(I::PARAMS, J::PARAMS), K::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let cutoff = data.input(&self.2, I::STATE_LEN);
+        let resonance = data.input(&self.3, I::STATE_LEN + J::STATE_LEN);
+        let period = data.chunk_step * consts::FRAC_32[1];
+        let offset = I::STATE_LEN + J::STATE_LEN + K::STATE_LEN;
+        let state = &mut data.state[offset..][..STATE_LEN];
+        let mut filter = [0.0; STATE_LEN];
+        let mut i = 0;
+
+        for (filter, state) in filter.iter_mut().zip(state.iter()) {
+            *filter = f32::from_bits(*state);
+        }
+
+        let chunk = chunk.for_each_sample(|sample| {
+            let outputs = ops::svf(
+                &mut filter,
+                *sample,
+                cutoff.0[i],
+                resonance.0[i],
+                period,
+            );
+
+            *sample = self.1.output(outputs);
+            i += 1;
+        });
+
+        for (state, filter) in state.iter_mut().zip(filter) {
+            *state = filter.to_bits();
+        }
+
+        chunk
+    }
+}
@@ -0,0 +1,40 @@
+use crate::tree::{Chunk, Data, Wave};
+
+/// External audio signal
+///
+/// Plays back audio fed into the [`Synth`](crate::tree::Synth) with
+/// [`Synth::feed()`](crate::tree::Synth::feed), for processing live audio
+/// (silence when the fed audio runs out).  Every `Input` in a waveform plays
+/// the same audio.
+///
+/// The [`next`](crate::next) equivalent is
+/// [`next::Wave::input()`](crate::next::Wave::input).
+///
+/// ```rust
+/// use fon::{chan::Ch32, Audio, Stream};
+/// use twang::tree::{filter::FilterType, line::{Input, Line}, Synth};
+///
+/// let waveform = Input.biquad(FilterType::Lowpass, Line(2_000.0), Line(0.7));
+/// let mut synth = Synth::new(waveform, []);
+/// let mut stream = Stream::<1>::new(48_000);
+/// let mut output = Audio::<Ch32, 1>::with_silence(48_000, 480);
+///
+/// // For each buffer from the microphone (stand-in at 44.1 kHz)
+/// let microphone = Audio::<Ch32, 1>::with_silence(44_100, 441);
+/// let mut input = Audio::<Ch32, 1>::with_silence(48_000, 480);
+///
+/// stream.pipe(&microphone, input.sink());
+/// synth.feed(&input);
+/// synth.stream(output.sink());
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Input;
+
+impl Wave for Input {
+    const STATE_LEN: usize = 0;
+    const PARAMS: usize = 0;
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        *data.input
+    }
+}
@@ -6,6 +6,7 @@ pub struct Line(pub f32);
 
 impl Wave for Line {
     const STATE_LEN: usize = 0;
+    const PARAMS: usize = 0;
 
     fn synthesize(&self, _data: &mut Data<'_>) -> Chunk {
         Chunk([self.0; 32])
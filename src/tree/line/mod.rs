@@ -2,10 +2,137 @@
 
 #![allow(clippy::module_inception)]
 
+const_postfix_waveform!(Input);
 const_postfix_waveform!(Line);
-const_postfix_waveform!(Param);
+const_postfix_waveform!(Source<'_>);
 
+impl<const N: usize> Param<N> {
+    const_postfix_waveform!();
+}
+
+mod input;
 mod line;
 mod param;
+mod source;
+
+pub use self::{input::Input, line::Line, param::Param, source::Source};
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use fon::{
+        chan::{Ch32, Channel},
+        pos::{Left, Mono, Right},
+        Audio, Frame,
+    };
+
+    use super::*;
+    use crate::{
+        ops,
+        tree::{filter::FilterType, testing::stream, Synth, Wave},
+    };
+
+    /// Get the number of parameters required by a waveform
+    fn params<W: Wave>(_wave: &W) -> usize {
+        W::PARAMS
+    }
+
+    /// Audio ramping up from 0 by 0.01 each sample
+    fn ramp(len: usize) -> Audio<Ch32, 1> {
+        Audio::with_f32_buffer(
+            48_000,
+            (0..len).map(|i| i as f32 * 0.01).collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn source() {
+        let recording = ramp(100);
+        let mut synth = Synth::new(Source(&recording), []);
+        let output = stream(&mut synth, 128);
+
+        assert_eq!(output[..100], ramp(100).as_f32_slice()[..]);
+        assert!(output[100..].iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn input() {
+        let mut synth = Synth::new(Input, []);
+        let stereo = Audio::<Ch32, 2>::with_frames(
+            48_000,
+            (0..50)
+                .map(|i| {
+                    Frame::<Ch32, 2>::new(Ch32::new(0.5), Ch32::new(i as f32))
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        synth.feed(&stereo);
+
+        let output = stream(&mut synth, 64);
+
+        for (frame, sample) in stereo.iter().zip(output.iter()) {
+            let mono = (frame[Left].to_f32() + frame[Right].to_f32()) * 0.5;
+
+            assert!((mono - sample).abs() < 0.0001, "{mono} != {sample}");
+        }
+        assert!(output[50..].iter().all(|x| *x == 0.0));
+
+        // Fed audio follows on from earlier audio
+        synth.feed(&ramp(40));
+        synth.feed(&ramp(40));
+
+        let output = stream(&mut synth, 96);
+
+        assert_eq!(output[..40], ramp(40).as_f32_slice()[..]);
+        assert_eq!(output[40..80], ramp(40).as_f32_slice()[..]);
+        assert_eq!(output[80..], [0.0; 16]);
+    }
+
+    #[test]
+    fn effects() {
+        let recording = Audio::<Ch32, 1>::with_f32_buffer(
+            48_000,
+            (0..4_800)
+                .map(|i| libm::sinf(i as f32 * 0.01))
+                .collect::<Vec<_>>(),
+        );
+        let filter = FilterType::Lowpass;
+        let mut biquad = ops::Biquad::new(filter);
+        let waveform =
+            Source(&recording).biquad(filter, Line(500.0), Line(0.7));
+        let output = stream(&mut Synth::new(waveform, []), 4_800);
+
+        for (frame, sample) in recording.iter().zip(output) {
+            let expected = biquad.step(frame[Mono], 500.0, 0.7).to_f32();
+
+            assert!((expected - sample).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn params_count() {
+        const PITCH: Param<3> = Param::new(0);
+
+        assert_eq!(params(&Line(440.0).osc().sine()), 0);
+        assert_eq!(params(&PITCH), 3);
+        assert_eq!(params(&PITCH.osc().bezier(Line(0.0)).clip()), 3);
+        assert_eq!(params(&Line(440.0).osc().pulse(Line(0.0), PITCH)), 3);
+    }
+
+    #[test]
+    fn param_smoothing() {
+        const GAIN: Param<2> = Param::new(1);
+
+        let mut synth = Synth::new(GAIN, [0.0, 0.5]);
 
-pub use self::{line::Line, param::Param};
+        assert_eq!(stream(&mut synth, 32), [0.5; 32]);
+        synth.params()[GAIN.index()] = -0.5;
+        assert_eq!(
+            stream(&mut synth, 32),
+            (0..32).map(|i| 0.5 - i as f32 / 32.0).collect::<Vec<_>>(),
+        );
+        assert_eq!(stream(&mut synth, 32), [-0.5; 32]);
+    }
+}
@@ -1,11 +1,60 @@
 use crate::tree::{Chunk, Data, Wave};
 
 /// Parameterized signal
+///
+/// `N` is the number of parameters the [`Synth`](crate::tree::Synth) is
+/// created with, so that parameters can't be used with the wrong synthesizer.
+/// A waveform using a `Param<N>` with a [`Synth`](crate::tree::Synth) with
+/// fewer than `N` parameters fails `cargo build`, but not `cargo check` (see
+/// [`Synth::new()`](crate::tree::Synth::new)).
+///
+/// ```rust
+/// use fon::{chan::Ch16, Audio};
+/// use twang::tree::{line::Param, Synth};
+///
+/// // Named parameter handles
+/// const PITCH: Param<2> = Param::new(0);
+/// const CURVE: Param<2> = Param::new(1);
+///
+/// let waveform = const { PITCH.osc().bezier(CURVE) };
+/// let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 48_000);
+/// let mut synth = Synth::new(waveform, [440.0, 0.0]);
+///
+/// synth.stream(audio.sink());
+/// // Raise pitch by an octave
+/// synth.params()[PITCH.index()] = 880.0;
+/// synth.stream(audio.sink());
+/// ```
 #[derive(Copy, Clone, Debug)]
-pub struct Param(usize);
+pub struct Param<const N: usize>(usize);
 
-impl Wave for Param {
+impl<const N: usize> Param<N> {
+    /// Create a handle to the parameter at `index`.
+    ///
+    /// ```rust,compile_fail
+    /// use twang::tree::line::Param;
+    ///
+    /// // Index is out of range for 2 parameters
+    /// const PARAM: Param<2> = Param::new(2);
+    /// ```
+    ///
+    /// # Panics
+    /// If `index` is not less than `N` (a compile error in a const context).
+    pub const fn new(index: usize) -> Self {
+        assert!(index < N, "Parameter index out of range");
+
+        Self(index)
+    }
+
+    /// Get the index of the parameter.
+    pub const fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl<const N: usize> Wave for Param<N> {
     const STATE_LEN: usize = 0;
+    const PARAMS: usize = N;
 
     fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
         data.params.chunk(self.0)
@@ -0,0 +1,53 @@
+use fon::{
+    chan::{Ch32, Channel},
+    pos::Mono,
+    Audio,
+};
+
+use crate::tree::{Chunk, Data, Wave};
+
+/// Recorded audio signal
+///
+/// Plays back audio (at the sample rate of the stream), followed by silence.
+/// Audio with more channels or at a different sample rate can be converted
+/// first with [`Audio::with_audio()`].
+///
+/// Twang files can't embed audio, so with [`next`](crate::next) synths feed
+/// the recording to [`next::Wave::input()`](crate::next::Wave::input)
+/// instead.
+///
+/// ```rust
+/// use fon::{chan::Ch32, Audio};
+/// use twang::tree::{filter::FilterType, line::{Line, Source}, Synth};
+///
+/// // Stand-in for a stereo recording of vocals at 44.1 kHz
+/// let recording = Audio::<Ch32, 2>::with_silence(44_100, 44_100);
+/// let vocals = Audio::<Ch32, 1>::with_audio(48_000, &recording);
+/// // Filter out the rumble
+/// let waveform =
+///     Source(&vocals).biquad(FilterType::Highpass, Line(80.0), Line(0.7));
+/// let mut audio = Audio::<Ch32, 1>::with_silence(48_000, 48_000);
+/// let mut synth = Synth::new(waveform, []);
+///
+/// synth.stream(audio.sink());
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Source<'a>(pub &'a Audio<Ch32, 1>);
+
+impl Wave for Source<'_> {
+    const STATE_LEN: usize = 1;
+    const PARAMS: usize = 0;
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let mut chunk = Chunk([0.0; 32]);
+        let cursor = data.state[0] as usize;
+        let frames = self.0.as_slice().iter().skip(cursor);
+
+        for (sample, frame) in chunk.0.iter_mut().zip(frames) {
+            *sample = frame[Mono].to_f32();
+        }
+
+        data.state[0] = data.state[0].saturating_add(32);
+        chunk
+    }
+}
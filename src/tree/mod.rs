@@ -65,6 +65,130 @@ macro_rules! const_postfix_waveform {
             crate::tree::osc::Bezier(self, curve)
         }
 
+        /// Postfix helper for wrapping synth instruction with
+        /// [`filter::Biquad`].
+        ///
+        /// [`filter::Biquad`]: crate::tree::filter::Biquad
+        pub const fn biquad<J, K>(
+            self,
+            filter: crate::tree::filter::FilterType,
+            cutoff: J,
+            q: K,
+        ) -> crate::tree::filter::Biquad<Self, J, K>
+        where
+            J: crate::tree::Wave,
+            K: crate::tree::Wave
+        {
+            crate::tree::filter::Biquad(self, filter, cutoff, q)
+        }
+
+        /// Postfix helper for wrapping synth instruction with
+        /// [`osc::BlPulse`].
+        ///
+        /// [`osc::BlPulse`]: crate::tree::osc::BlPulse
+        pub const fn bl_pulse<J>(
+            self,
+            duty: J,
+        ) -> crate::tree::osc::BlPulse<Self, J>
+        where
+            J: crate::tree::Wave
+        {
+            crate::tree::osc::BlPulse(self, duty)
+        }
+
+        /// Postfix helper for wrapping synth instruction with
+        /// [`osc::BlSawtooth`].
+        ///
+        /// [`osc::BlSawtooth`]: crate::tree::osc::BlSawtooth
+        pub const fn bl_sawtooth(self) -> crate::tree::osc::BlSawtooth<Self> {
+            crate::tree::osc::BlSawtooth(self)
+        }
+
+        /// Postfix helper for wrapping synth instruction with
+        /// [`osc::BlTriangle`].
+        ///
+        /// [`osc::BlTriangle`]: crate::tree::osc::BlTriangle
+        pub const fn bl_triangle(self) -> crate::tree::osc::BlTriangle<Self> {
+            crate::tree::osc::BlTriangle(self)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`ops::Clip`].
+        ///
+        /// [`ops::Clip`]: crate::tree::ops::Clip
+        pub const fn clip(self) -> crate::tree::ops::Clip<Self> {
+            crate::tree::ops::Clip(self)
+        }
+
+        /// Postfix helper for wrapping synth instruction with
+        /// [`envelope::Envelope`].
+        ///
+        /// [`envelope::Envelope`]: crate::tree::envelope::Envelope
+        pub const fn envelope(
+            self,
+            press: &'static [crate::tree::envelope::Segment],
+            release: &'static [crate::tree::envelope::Segment],
+            trigger: crate::tree::envelope::Trigger,
+        ) -> crate::tree::envelope::Envelope<Self> {
+            crate::tree::envelope::Envelope(self, press, release, trigger)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`ops::Far`].
+        ///
+        /// [`ops::Far`]: crate::tree::ops::Far
+        pub const fn far<J>(self, limit: J) -> crate::tree::ops::Far<Self, J>
+        where
+            J: crate::tree::Wave
+        {
+            crate::tree::ops::Far(self, limit)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`ops::Max`].
+        ///
+        /// [`ops::Max`]: crate::tree::ops::Max
+        pub const fn max<J>(self, other: J) -> crate::tree::ops::Max<Self, J>
+        where
+            J: crate::tree::Wave
+        {
+            crate::tree::ops::Max(self, other)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`ops::Min`].
+        ///
+        /// [`ops::Min`]: crate::tree::ops::Min
+        pub const fn min<J>(self, other: J) -> crate::tree::ops::Min<Self, J>
+        where
+            J: crate::tree::Wave
+        {
+            crate::tree::ops::Min(self, other)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`ops::Near`].
+        ///
+        /// [`ops::Near`]: crate::tree::ops::Near
+        pub const fn near<J>(self, limit: J) -> crate::tree::ops::Near<Self, J>
+        where
+            J: crate::tree::Wave
+        {
+            crate::tree::ops::Near(self, limit)
+        }
+
+        /// Postfix helper for wrapping synth instruction with
+        /// [`filter::Ladder`].
+        ///
+        /// [`filter::Ladder`]: crate::tree::filter::Ladder
+        pub const fn ladder<J, K>(
+            self,
+            mode: crate::tree::filter::Mode,
+            cutoff: J,
+            resonance: K,
+        ) -> crate::tree::filter::Ladder<Self, J, K>
+        where
+            J: crate::tree::Wave,
+            K: crate::tree::Wave
+        {
+            crate::tree::filter::Ladder(self, mode, cutoff, resonance)
+        }
+
         /// Postfix helper for wrapping synth instruction with [`osc::Osc`].
         ///
         /// [`osc::Osc`]: crate::tree::osc::Osc
@@ -72,6 +196,23 @@ macro_rules! const_postfix_waveform {
             crate::tree::osc::Osc(self)
         }
 
+        /// Postfix helper for wrapping synth instruction with [`osc::Phase`].
+        ///
+        /// [`osc::Phase`]: crate::tree::osc::Phase
+        pub const fn phase<J>(self, offset: J) -> crate::tree::osc::Phase<Self, J>
+        where
+            J: crate::tree::Wave
+        {
+            crate::tree::osc::Phase(self, offset)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`noise::Pink`].
+        ///
+        /// [`noise::Pink`]: crate::tree::noise::Pink
+        pub const fn pink(self) -> crate::tree::noise::Pink<Self> {
+            crate::tree::noise::Pink(self)
+        }
+
         /// Postfix helper for wrapping synth instruction with [`osc::Pulse`].
         ///
         /// [`osc::Pulse`]: crate::tree::osc::Pulse
@@ -86,12 +227,97 @@ macro_rules! const_postfix_waveform {
             crate::tree::osc::Pulse(self, duty, alias)
         }
 
+        /// Postfix helper for wrapping synth instruction with
+        /// [`osc::ShapedSine`].
+        ///
+        /// [`osc::ShapedSine`]: crate::tree::osc::ShapedSine
+        pub const fn shaped_sine<J, K, L>(
+            self,
+            duty: J,
+            zero: K,
+            peak: L,
+        ) -> crate::tree::osc::ShapedSine<Self, J, K, L>
+        where
+            J: crate::tree::Wave,
+            K: crate::tree::Wave,
+            L: crate::tree::Wave
+        {
+            crate::tree::osc::ShapedSine(self, duty, zero, peak)
+        }
+
         /// Postfix helper for wrapping synth instruction with [`osc::Sine`].
         ///
         /// [`osc::Sine`]: crate::tree::osc::Sine
         pub const fn sine(self) -> crate::tree::osc::Sine<Self> {
             crate::tree::osc::Sine(self)
         }
+
+        /// Postfix helper for wrapping synth instruction with [`filter::Svf`].
+        ///
+        /// [`filter::Svf`]: crate::tree::filter::Svf
+        pub const fn svf<J, K>(
+            self,
+            mode: crate::tree::filter::Mode,
+            cutoff: J,
+            resonance: K,
+        ) -> crate::tree::filter::Svf<Self, J, K>
+        where
+            J: crate::tree::Wave,
+            K: crate::tree::Wave
+        {
+            crate::tree::filter::Svf(self, mode, cutoff, resonance)
+        }
+
+        /// Postfix helper for wrapping synth instruction with
+        /// [`osc::Wavetable`].
+        ///
+        /// [`osc::Wavetable`]: crate::tree::osc::Wavetable
+        pub const fn wavetable<J>(
+            self,
+            wavetable: &crate::osc::Wavetable,
+            position: J,
+        ) -> crate::tree::osc::Wavetable<'_, Self, J>
+        where
+            J: crate::tree::Wave
+        {
+            crate::tree::osc::Wavetable(self, wavetable, position)
+        }
+
+        /// Postfix helper for wrapping synth instruction with
+        /// [`osc::Waypoints`].
+        ///
+        /// [`osc::Waypoints`]: crate::tree::osc::Waypoints
+        pub const fn waypoints(
+            self,
+            points: &[crate::osc::Waypoint],
+            transition: crate::osc::Transition,
+        ) -> crate::tree::osc::Waypoints<'_, Self> {
+            crate::tree::osc::Waypoints(self, points, transition)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`noise::White`].
+        ///
+        /// [`noise::White`]: crate::tree::noise::White
+        pub const fn white(self) -> crate::tree::noise::White<Self> {
+            crate::tree::noise::White(self)
+        }
+
+        /// Postfix helper for wrapping synth instruction with [`osc::Zoid`].
+        ///
+        /// [`osc::Zoid`]: crate::tree::osc::Zoid
+        pub const fn zoid<J, K, L>(
+            self,
+            rise: J,
+            hold: K,
+            fall: L,
+        ) -> crate::tree::osc::Zoid<Self, J, K, L>
+        where
+            J: crate::tree::Wave,
+            K: crate::tree::Wave,
+            L: crate::tree::Wave
+        {
+            crate::tree::osc::Zoid(self, rise, hold, fall)
+        }
     };
     ($type:ty) => {
         impl $type {
@@ -107,8 +333,13 @@ macro_rules! const_postfix_waveform {
 
 mod chunk;
 mod consts;
+#[cfg(test)]
 mod conversions;
+pub mod envelope;
+pub mod filter;
 pub mod line;
+pub mod noise;
+pub mod ops;
 pub mod osc;
 mod params;
 mod synth;
@@ -122,18 +353,43 @@ use self::{
 
 /// Trait implemented by all waveforms
 #[traitful::seal(
+    line::Input,
     line::Line,
-    line::Param,
+    for<const N: usize> line::Param<N>,
+    line::Source<'_>,
     for<T: Wave> &T,
+    for<T: Wave> envelope::Envelope<T>,
+    for<T: Wave, U: Wave, V: Wave> filter::Biquad<T, U, V>,
+    for<T: Wave, U: Wave, V: Wave> filter::Ladder<T, U, V>,
+    for<T: Wave, U: Wave, V: Wave> filter::Svf<T, U, V>,
+    for<T: Wave> noise::Pink<T>,
+    for<T: Wave> noise::White<T>,
+    for<T: Wave> ops::Clip<T>,
+    for<T: Wave, U: Wave> ops::Far<T, U>,
+    for<T: Wave, U: Wave> ops::Max<T, U>,
+    for<T: Wave, U: Wave> ops::Min<T, U>,
+    for<T: Wave, U: Wave> ops::Near<T, U>,
     for<T: Wave, U: Wave> osc::Bezier<T, U>,
+    for<T: Wave, U: Wave> osc::BlPulse<T, U>,
+    for<T: Wave> osc::BlSawtooth<T>,
+    for<T: Wave> osc::BlTriangle<T>,
     for<T: Wave> osc::Osc<T>,
+    for<T: Wave, U: Wave> osc::Phase<T, U>,
     for<T: Wave, U: Wave, V: Wave> osc::Pulse<T, U, V>,
+    for<T: Wave, U: Wave, V: Wave, W: Wave> osc::ShapedSine<T, U, V, W>,
     for<T: Wave> osc::Sine<T>,
+    for<T: Wave, U: Wave> osc::Wavetable<'_, T, U>,
+    for<T: Wave> osc::Waypoints<'_, T>,
+    for<T: Wave, U: Wave, V: Wave, W: Wave> osc::Zoid<T, U, V, W>,
 )]
 pub trait Wave {
     /// Number of 32-bit states required for this waveform
     const STATE_LEN: usize;
 
+    /// Number of user parameters required for this waveform (one more than the
+    /// highest parameter index)
+    const PARAMS: usize;
+
     /// Synthesize a chunk of audio.
     #[must_use]
     #[doc(hidden)]
@@ -145,8 +401,56 @@ where
     T: Wave,
 {
     const STATE_LEN: usize = T::STATE_LEN;
+    const PARAMS: usize = T::PARAMS;
 
     fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
         (**self).synthesize(data)
     }
 }
+
+/// Get the number of user parameters required by two waveforms
+const fn params(a: usize, b: usize) -> usize {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Helpers for testing waveforms
+#[cfg(test)]
+pub(crate) mod testing {
+    use alloc::vec::Vec;
+
+    use fon::{chan::Ch32, Audio};
+
+    use super::{Synth, Wave};
+
+    /// Stream `len` samples from a synth at 48 kHz
+    pub(crate) fn stream<W: Wave, const N: usize>(
+        synth: &mut Synth<W, N>,
+        len: usize,
+    ) -> Vec<f32> {
+        let mut audio = Audio::<Ch32, 1>::with_silence(48_000, len);
+
+        synth.stream(audio.sink());
+        audio.as_f32_slice().to_vec()
+    }
+
+    /// Render one second of a waveform at 48 kHz
+    pub(crate) fn render(wave: impl Wave) -> Vec<f32> {
+        stream(&mut Synth::new(wave, []), 48_000)
+    }
+
+    /// Check that samples at 48 kHz are close to a function of time (seconds)
+    pub(crate) fn assert_near(samples: &[f32], f: impl Fn(f64) -> f64) {
+        for (i, &sample) in samples.iter().enumerate() {
+            let expected = f(i as f64 / 48_000.0);
+
+            assert!(
+                (f64::from(sample) - expected).abs() < 0.001,
+                "sample {i}: {sample} != {expected}",
+            );
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Noise generators
+
+#![allow(clippy::module_inception)]
+
+const_postfix_waveform!(Pink<T>, T);
+const_postfix_waveform!(White<T>, T);
+
+mod pink;
+mod white;
+
+pub use self::{pink::Pink, white::White};
+use crate::{noise::Generator, tree::Chunk};
+
+/// Number of 32-bit states used for noise, besides the generator's own
+const STATE_LEN: usize = 2;
+
+/// Synthesize seeded noise from a chunk of keys, restarting the sequence
+/// whenever the key changes.
+///
+/// The state holds whether or not the generator has been seeded, the key it
+/// was seeded with, and then the generator state.
+fn noise<G: Generator>(keys: Chunk, state: &mut [u32]) -> Chunk {
+    let (seed, state) = state.split_at_mut(STATE_LEN);
+    let mut generator = (seed[0] != 0).then(|| G::load(state));
+    let chunk = keys.for_each_sample(|sample| {
+        let key = sample.to_bits();
+
+        if seed[0] == 0 || seed[1] != key {
+            seed.copy_from_slice(&[1, key]);
+            generator = Some(G::with_seed(key));
+        }
+
+        *sample = generator.as_mut().unwrap().step().into();
+    });
+
+    if let Some(generator) = generator {
+        generator.store(state);
+    }
+
+    chunk
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::Generator;
+    use crate::{
+        noise,
+        tree::{line::Line, testing::render},
+    };
+
+    /// Generate one second of samples at 48 kHz
+    fn sequence<G: Generator>(seed: f32) -> Vec<f32> {
+        let mut generator = G::with_seed(seed.to_bits());
+
+        (0..48_000).map(|_| generator.step().into()).collect()
+    }
+
+    #[test]
+    fn seeded() {
+        assert_eq!(render(Line(0.0).white()), sequence::<noise::White>(0.0));
+        assert_eq!(render(Line(0.0).pink()), sequence::<noise::Pink>(0.0));
+        assert_eq!(render(Line(5.0).white()), sequence::<noise::White>(5.0));
+        assert_eq!(render(Line(5.0).pink()), sequence::<noise::Pink>(5.0));
+    }
+
+    #[test]
+    fn nested() {
+        let mut phases = sequence::<noise::White>(2.0);
+
+        for phase in &mut phases {
+            *phase = -libm::cosf(*phase * core::f32::consts::PI);
+        }
+
+        assert_eq!(render(Line(2.0).white().sine()), phases);
+    }
+
+    #[test]
+    fn key_change() {
+        let keys = render(Line(440.0).osc());
+        let noise: Vec<f32> = keys
+            .iter()
+            .map(|key| noise::White::with_seed(key.to_bits()).step().into())
+            .collect();
+
+        assert_eq!(render(Line(440.0).osc().white()), noise);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{
+    noise::{self, Generator},
+    tree::{Chunk, Data, Wave},
+};
+
+/// Pink noise
+///
+/// Takes random key as input; the noise sequence is seeded from the key, and
+/// restarts whenever the key changes.
+#[derive(Debug)]
+pub struct Pink<I>(pub I);
+
+impl<I> Wave for Pink<I>
+where
+    I: Wave,
+{
+    const STATE_LEN: usize =
+        I::STATE_LEN + super::STATE_LEN + noise::Pink::STATE_LEN;
+    const PARAMS: usize = I::PARAMS;
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let keys = self.0.synthesize(data);
+
+        super::noise::<noise::Pink>(keys, &mut data.state[I::STATE_LEN..])
+    }
+}
@@ -0,0 +1,19 @@
+use crate::tree::{Chunk, Data, Wave};
+
+/// Hard clipping
+///
+/// Takes audio as input, and clamps it from -1 to 1
+#[derive(Debug)]
+pub struct Clip<I>(pub I);
+
+impl<I> Wave for Clip<I>
+where
+    I: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN;
+    const PARAMS: usize = I::PARAMS;
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        self.0.synthesize(data).clip()
+    }
+}
@@ -0,0 +1,24 @@
+use crate::tree::{params, Chunk, Data, Wave};
+
+/// Limit amplitude to be no nearer to zero than another wave
+///
+/// Takes audio and limit (sign is ignored, and zero is treated as positive) as
+/// input
+#[derive(Debug)]
+pub struct Far<I, J>(pub I, pub J);
+
+impl<I, J> Wave for Far<I, J>
+where
+    I: Wave,
+    J: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN;
+    const PARAMS: usize = params(I::PARAMS, J::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let limit = data.input(&self.1, I::STATE_LEN);
+
+        chunk.far(limit)
+    }
+}
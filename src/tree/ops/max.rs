@@ -0,0 +1,24 @@
+use crate::tree::{params, Chunk, Data, Wave};
+
+/// Maximum value of two waves (-0.25 is greater than -0.5; for maximum
+/// amplitude use [`Far`](super::Far))
+///
+/// Takes two audio waves as input
+#[derive(Debug)]
+pub struct Max<I, J>(pub I, pub J);
+
+impl<I, J> Wave for Max<I, J>
+where
+    I: Wave,
+    J: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN;
+    const PARAMS: usize = params(I::PARAMS, J::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let other = data.input(&self.1, I::STATE_LEN);
+
+        chunk.max(other)
+    }
+}
@@ -0,0 +1,24 @@
+use crate::tree::{params, Chunk, Data, Wave};
+
+/// Minimum value of two waves (-0.5 is less than -0.25; for minimum amplitude
+/// use [`Near`](super::Near))
+///
+/// Takes two audio waves as input
+#[derive(Debug)]
+pub struct Min<I, J>(pub I, pub J);
+
+impl<I, J> Wave for Min<I, J>
+where
+    I: Wave,
+    J: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN;
+    const PARAMS: usize = params(I::PARAMS, J::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let other = data.input(&self.1, I::STATE_LEN);
+
+        chunk.min(other)
+    }
+}
@@ -0,0 +1,66 @@
+//! Signal operations (waveshapers)
+
+const_postfix_waveform!(Clip<T>, T);
+const_postfix_waveform!(Far<T, U>, T, U);
+const_postfix_waveform!(Max<T, U>, T, U);
+const_postfix_waveform!(Min<T, U>, T, U);
+const_postfix_waveform!(Near<T, U>, T, U);
+
+mod clip;
+mod far;
+mod max;
+mod min;
+mod near;
+
+pub use self::{clip::Clip, far::Far, max::Max, min::Min, near::Near};
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use fon::chan::{Ch32, Channel};
+
+    use super::*;
+    use crate::{
+        ops,
+        tree::{line::Line, testing::render},
+    };
+
+    /// Apply a per-sample operation to two rendered waves
+    fn zip(a: Vec<f32>, b: Vec<f32>, f: fn(Ch32, Ch32) -> Ch32) -> Vec<f32> {
+        a.into_iter()
+            .zip(b)
+            .map(|(a, b)| f(a.into(), b.into()).to_f32())
+            .collect()
+    }
+
+    #[test]
+    fn matches_legacy_ops() {
+        let sine = Line(440.0).osc().sine();
+        let saw = Line(110.0).osc();
+
+        assert_eq!(
+            render(Min(&sine, &saw)),
+            zip(render(&sine), render(&saw), |a, b| ops::Min.step(a, b)),
+        );
+        assert_eq!(
+            render(Max(&sine, &saw)),
+            zip(render(&sine), render(&saw), |a, b| ops::Max.step(a, b)),
+        );
+        assert_eq!(
+            render(Near(&sine, &saw)),
+            zip(render(&sine), render(&saw), |a, b| ops::Near.step(a, b)),
+        );
+        assert_eq!(
+            render(Far(&sine, &saw)),
+            zip(render(&sine), render(&saw), |a, b| ops::Far.step(a, b)),
+        );
+    }
+
+    #[test]
+    fn clip() {
+        assert!(render(Line(2.0).clip()).iter().all(|x| *x == 1.0));
+        assert!(render(Line(-2.0).clip()).iter().all(|x| *x == -1.0));
+        assert!(render(Line(0.5).clip()).iter().all(|x| *x == 0.5));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::tree::{Chunk, Data, Wave};
+use crate::tree::{params, Chunk, Data, Wave};
 
 /// Bezier wave
 ///
@@ -12,10 +12,11 @@ where
     J: Wave,
 {
     const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN;
+    const PARAMS: usize = params(I::PARAMS, J::PARAMS);
 
     fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
-        let chunk = self.0.synthesize(data);
-        let curve = self.1.synthesize(data);
+        let chunk = data.input(&self.0, 0);
+        let curve = data.input(&self.1, I::STATE_LEN);
         let old = chunk.neg_abs();
 
         old.offset(1.0)
@@ -25,3 +26,11 @@ where
             .copysign(chunk)
     }
 }
+
+/// Bend one sample of phase (-1 to 1) by a curve (-1 to 1)
+#[inline(always)]
+pub(crate) fn bezier(phase: f32, curve: f32) -> f32 {
+    let old = -phase.abs();
+
+    libm::copysignf((old + 1.0) * curve * old + old, phase)
+}
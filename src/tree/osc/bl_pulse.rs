@@ -0,0 +1,41 @@
+use crate::{
+    osc::blep,
+    tree::{consts, params, Chunk, Data, Wave},
+};
+
+/// Band-limited pulse wave
+///
+/// Takes frequency (hertz) and duty (-1 to 1) as input
+///
+/// A duty of 0 is a square wave, -1 is a constant -1 and 1 is a constant 1.
+/// Both edges are smoothed out by a PolyBLEP to greatly reduce aliasing at
+/// high pitches.
+#[derive(Debug)]
+pub struct BlPulse<I, J>(pub I, pub J);
+
+impl<I, J> Wave for BlPulse<I, J>
+where
+    I: Wave,
+    J: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN + 1;
+    const PARAMS: usize = params(I::PARAMS, J::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let period = data.chunk_step * consts::FRAC_32[1];
+        let duty = data.input(&self.1, 1 + I::STATE_LEN);
+        let mut phase = f32::from_bits(data.state[0]);
+        let mut i = 0;
+        let chunk = data.input(&self.0, 1).for_each_sample(|sample| {
+            let dt = *sample * period;
+            let width = (duty.0[i].clamp(-1.0, 1.0) + 1.0) * 0.5;
+
+            *sample = blep::pulse(phase, dt, width);
+            phase = (phase + dt) % 1.0;
+            i += 1;
+        });
+
+        data.state[0] = phase.to_bits();
+        chunk
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{
+    osc::blep,
+    tree::{consts, Chunk, Data, Wave},
+};
+
+/// Band-limited triangle wave
+///
+/// Takes frequency (hertz) as input
+///
+/// Starts at 1, reaching -1 halfway through each cycle, with the corners
+/// rounded off by a PolyBLAMP to reduce aliasing at high pitches.
+#[derive(Debug)]
+pub struct BlTriangle<I>(pub I);
+
+impl<I> Wave for BlTriangle<I>
+where
+    I: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + 1;
+    const PARAMS: usize = I::PARAMS;
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let period = data.chunk_step * consts::FRAC_32[1];
+        let mut phase = f32::from_bits(data.state[0]);
+        let chunk = data.input(&self.0, 1).for_each_sample(|sample| {
+            let dt = *sample * period;
+
+            *sample = blep::triangle(phase, dt);
+            phase = (phase + dt) % 1.0;
+        });
+
+        data.state[0] = phase.to_bits();
+        chunk
+    }
+}
@@ -3,13 +3,262 @@
 #![allow(clippy::module_inception)]
 
 const_postfix_waveform!(Bezier<T, U>, T, U);
+const_postfix_waveform!(BlPulse<T, U>, T, U);
+const_postfix_waveform!(BlSawtooth<T>, T);
+const_postfix_waveform!(BlTriangle<T>, T);
 const_postfix_waveform!(Osc<T>, T);
+const_postfix_waveform!(Phase<T, U>, T, U);
 const_postfix_waveform!(Pulse<T, U, V>, T, U, V);
+const_postfix_waveform!(ShapedSine<T, U, V, W>, T, U, V, W);
 const_postfix_waveform!(Sine<T>, T);
+const_postfix_waveform!(Wavetable<'_, T, U>, T, U);
+const_postfix_waveform!(Waypoints<'_, T>, T);
+const_postfix_waveform!(Zoid<T, U, V, W>, T, U, V, W);
 
 mod bezier;
+mod bl_pulse;
+mod bl_sawtooth;
+mod bl_triangle;
 mod osc;
+mod phase;
 mod pulse;
+mod shaped_sine;
 mod sine;
+mod wavetable;
+mod waypoints;
+mod zoid;
 
-pub use self::{bezier::Bezier, osc::Osc, pulse::Pulse, sine::Sine};
+pub use self::{
+    bezier::Bezier, bl_pulse::BlPulse, bl_sawtooth::BlSawtooth,
+    bl_triangle::BlTriangle, osc::Osc, phase::Phase, pulse::Pulse,
+    shaped_sine::ShapedSine, sine::Sine, wavetable::Wavetable,
+    waypoints::Waypoints, zoid::Zoid,
+};
+
+pub(crate) use self::{bezier::bezier, zoid::zoid};
+
+#[cfg(feature = "muon")]
+pub(crate) use self::shaped_sine::sine_phase;
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use fon::chan::Ch32;
+
+    use crate::{
+        osc,
+        tree::{
+            line::Line,
+            testing::{assert_near, render},
+        },
+    };
+
+    fn assert_close(a: Vec<f32>, b: impl Iterator<Item = Ch32>) {
+        for (a, b) in a.into_iter().zip(b.map(f32::from)) {
+            assert!((a - b).abs() < 0.001, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn band_limited_matches_ops() {
+        let mut saw = osc::BlSawtooth::new();
+        let mut tri = osc::BlTriangle::new();
+        let mut pulse = osc::BlPulse::new();
+        let duty = Ch32::new(0.4);
+
+        assert_close(
+            render(Line(3_000.0).bl_sawtooth()),
+            (0..48_000).map(|_| saw.step(3_000.0)),
+        );
+        assert_close(
+            render(Line(3_000.0).bl_triangle()),
+            (0..48_000).map(|_| tri.step(3_000.0)),
+        );
+        assert_close(
+            render(Line(3_000.0).bl_pulse(Line(0.4))),
+            (0..48_000).map(|_| pulse.step(3_000.0, duty)),
+        );
+    }
+
+    #[test]
+    fn phase_offset() {
+        use core::f64::consts::TAU;
+
+        // First 0.1 seconds, before single precision phase error builds up
+        let phased = |offset: f32| {
+            render(Line(440.0).osc().phase(Line(offset)).sine())[..4_800]
+                .to_vec()
+        };
+
+        assert_near(&phased(0.0), |t| libm::cos(TAU * 440.0 * t));
+        assert_near(&phased(0.25), |t| libm::cos(TAU * (440.0 * t + 0.25)));
+        assert_near(&phased(-0.25), |t| libm::cos(TAU * (440.0 * t - 0.25)));
+    }
+
+    #[test]
+    fn phase_modulation() {
+        use core::f64::consts::TAU;
+
+        let modulator = Line(660.0).osc().sine();
+        let samples = render(Line(440.0).osc().phase(modulator).sine());
+
+        // First 0.1 seconds, before single precision phase error builds up
+        assert_near(&samples[..4_800], |t| {
+            libm::cos(TAU * (440.0 * t + libm::cos(TAU * 660.0 * t)))
+        });
+    }
+
+    #[test]
+    fn shaped_sine_defaults() {
+        for hz in [1.0, 440.0, 3_000.0] {
+            let modulator = Line(hz * 1.5).osc().sine();
+
+            assert_eq!(
+                render(Line(hz).osc().sine()),
+                render(Line(hz).osc().shaped_sine(
+                    Line(0.0),
+                    Line(0.0),
+                    Line(0.0)
+                )),
+            );
+            assert_eq!(
+                render(Line(hz).osc().phase(&modulator).sine()),
+                render(Line(hz).osc().phase(&modulator).shaped_sine(
+                    Line(0.0),
+                    Line(0.0),
+                    Line(0.0)
+                )),
+            );
+        }
+    }
+
+    #[test]
+    fn shaped_sine_duty() {
+        for duty in [-0.5, 0.0, 0.5] {
+            let samples = render(Line(100.0).osc().shaped_sine(
+                Line(duty),
+                Line(0.0),
+                Line(0.0),
+            ));
+            let high = samples.iter().filter(|x| **x > 0.0).count();
+
+            // Same fraction of time high as a pulse wave
+            let expected = (1.0 + duty) / 2.0 * 48_000.0;
+            assert!((high as f32 - expected).abs() < 200.0, "{duty}: {high}");
+        }
+    }
+
+    #[test]
+    fn shaped_sine_slopes() {
+        let shaped = |zero: f32, peak: f32| {
+            render(Line(100.0).osc().shaped_sine(
+                Line(0.0),
+                Line(zero),
+                Line(peak),
+            ))
+        };
+        // Slope at the first zero crossing
+        let crossing = |samples: Vec<f32>| {
+            let i = samples.iter().position(|x| *x < 0.0).unwrap();
+
+            samples[i - 1] - samples[i]
+        };
+        // Distance from the first peak, one sample later
+        let peak = |samples: Vec<f32>| 1.0 - samples[1];
+        let sine = render(Line(100.0).osc().sine());
+
+        for (zero, ratio) in [(1.0, 2.0), (0.5, 1.5), (-0.5, 0.5)] {
+            let measured = crossing(shaped(zero, 0.0)) / crossing(sine.clone());
+
+            assert!((measured - ratio).abs() < 0.05, "{zero}: {measured}");
+        }
+
+        // Curvature at the peak is scaled by the square of the slope
+        let sharp = peak(shaped(0.0, 1.0)) / peak(sine.clone());
+        let flat = peak(shaped(0.0, -1.0)) / peak(sine);
+
+        assert!((3.8..4.1).contains(&sharp), "{sharp}");
+        assert!(flat < 0.01, "{flat}");
+    }
+
+    #[test]
+    fn zoid_special_cases() {
+        // Location in the cycle (-1 at the start to 1 at the end)
+        let locations: Vec<f32> =
+            render(Line(440.0).osc()).into_iter().map(|x| -x).collect();
+        let zoid = |rise: f32, hold: f32, fall: f32| {
+            render(Line(440.0).osc().zoid(Line(rise), Line(hold), Line(fall)))
+        };
+        let check = |rise, hold, fall, f: fn(f32) -> f32| {
+            let expected = locations.iter().map(|x| Ch32::new(f(*x)));
+
+            assert_close(zoid(rise, hold, fall), expected);
+        };
+
+        // Downward sawtooth (the same as the phase)
+        check(-1.0, -1.0, -1.0, |x| -x);
+        // Upward sawtooth
+        check(-1.0, 1.0, 1.0, |x| x);
+        // Constant 1
+        check(-1.0, -1.0, 1.0, |_| 1.0);
+        // Triangle
+        check(-1.0, 0.0, 0.0, |x| 1.0 - 2.0 * x.abs());
+        // Square
+        check(0.0, 0.0, 1.0, |x| if x < 0.0 { -1.0 } else { 1.0 });
+        // Constant -1
+        check(1.0, 1.0, 1.0, |_| -1.0);
+
+        // Out of order locations are moved up to the previous one
+        assert_eq!(zoid(0.5, -1.0, 0.0), zoid(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn zoid_phase_offset_square() {
+        // Phase offsets round onto the end of the cycle
+        let samples = render(Line(1_000.0).osc().phase(Line(-0.25)).zoid(
+            Line(0.0),
+            Line(0.0),
+            Line(1.0),
+        ));
+
+        assert!(samples.iter().all(|x| *x == 1.0 || *x == -1.0));
+    }
+
+    #[test]
+    fn wavetable_matches_ops() {
+        let square: Vec<f32> =
+            (0..64).map(|i| if i < 32 { 1.0 } else { -1.0 }).collect();
+        let saw: Vec<f32> = (0..128).map(|i| 1.0 - i as f32 / 64.0).collect();
+        let wavetable = osc::Wavetable::new(&[&square, &saw]);
+        let mut table = osc::TableOsc::new(&wavetable);
+        let position = Ch32::new(0.3);
+
+        assert_close(
+            render(Line(3_000.0).wavetable(&wavetable, Line(0.3))),
+            (0..48_000).map(|_| table.step(3_000.0, position)),
+        );
+    }
+
+    #[test]
+    fn waypoints_matches_ops() {
+        const POINTS: [osc::Waypoint; 3] = [
+            osc::Waypoint::new(0.1, 1.0),
+            osc::Waypoint::new(0.4, -0.5),
+            osc::Waypoint::new(0.8, 0.0),
+        ];
+
+        for transition in [
+            osc::Transition::Jump,
+            osc::Transition::Linear,
+            osc::Transition::Bezier(-0.5),
+        ] {
+            let mut ways = osc::Waypoints::new(&POINTS, transition);
+
+            assert_close(
+                render(Line(3_000.0).osc().waypoints(&POINTS, transition)),
+                (0..48_000).map(|_| ways.step(3_000.0)),
+            );
+        }
+    }
+}
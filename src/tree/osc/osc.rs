@@ -14,13 +14,13 @@ where
     I: Wave,
 {
     const STATE_LEN: usize = I::STATE_LEN + 1;
+    const PARAMS: usize = I::PARAMS;
 
     fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
         let mut i = 0;
         let mut phase = f32::from_bits(data.state[0]);
-        let chunk = self
-            .0
-            .synthesize(data)
+        let chunk = data
+            .input(&self.0, 1)
             .for_each_sample(|sample| {
                 let frequency = *sample;
 
@@ -0,0 +1,36 @@
+use crate::tree::{params, Chunk, Data, Wave};
+
+/// Phase offset
+///
+/// Takes phase (-1 to 1) and offset (in cycles) as input, and outputs the
+/// shifted phase for an oscillator to use.
+#[derive(Debug)]
+pub struct Phase<I, J>(pub I, pub J);
+
+impl<I, J> Wave for Phase<I, J>
+where
+    I: Wave,
+    J: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN;
+    const PARAMS: usize = params(I::PARAMS, J::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let offset = data.input(&self.1, I::STATE_LEN);
+
+        // Convert to 0 to 1 cycle, offset, then convert back
+        chunk
+            .gain(-0.5)
+            .offset(0.5)
+            .mix(offset)
+            .for_each_sample(|sample| {
+                *sample %= 1.0;
+                if *sample < 0.0 {
+                    *sample += 1.0;
+                }
+            })
+            .gain(-2.0)
+            .offset(1.0)
+    }
+}
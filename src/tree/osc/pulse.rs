@@ -1,8 +1,11 @@
-use crate::tree::{Chunk, Data, Wave};
+use crate::tree::{params, Chunk, Data, Wave};
 
 /// Pulse wave
 ///
 /// Takes phase (-1 to 1), duty (-1 to 1) and alias (0 to 1) as input
+///
+/// Alias is the slope of the edges (0 is a pulse, 1 a triangle), not
+/// anti-aliasing; see [`BlPulse`](super::BlPulse) for a band-limited pulse
 #[derive(Debug)]
 pub struct Pulse<I, J, K>(pub I, pub J, pub K);
 
@@ -13,11 +16,12 @@ where
     K: Wave,
 {
     const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN + K::STATE_LEN;
+    const PARAMS: usize = params(params(I::PARAMS, J::PARAMS), K::PARAMS);
 
     fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
-        let chunk = self.0.synthesize(data);
-        let cycle = self.1.synthesize(data);
-        let alias = self.2.synthesize(data);
+        let chunk = data.input(&self.0, 0);
+        let cycle = data.input(&self.1, I::STATE_LEN);
+        let alias = data.input(&self.2, I::STATE_LEN + J::STATE_LEN);
         let clip = alias.recip();
         let pulse = chunk
             .abs()
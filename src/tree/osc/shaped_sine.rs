@@ -0,0 +1,74 @@
+use core::f32::consts;
+
+use crate::tree::{params, Chunk, Data, Wave};
+
+/// Shaped sine wave
+///
+/// Takes phase (-1 to 1), duty (-1 to 1), zero (-1 to 1) and peak (-1 to 1) as
+/// input
+///
+/// - Duty moves the zero crossings, the same as for [`Pulse`](super::Pulse)
+///   (positive is more time above zero)
+/// - Zero changes the slope at the zero crossings (positive is faster, -1 is
+///   flat)
+/// - Peak changes the slope near the peaks (positive is sharper, -1 is
+///   flatter)
+///
+/// With all three at 0, this is exactly the same as [`Sine`](super::Sine).
+#[derive(Debug)]
+pub struct ShapedSine<I, J, K, L>(pub I, pub J, pub K, pub L);
+
+impl<I, J, K, L> Wave for ShapedSine<I, J, K, L>
+where
+    I: Wave,
+    J: Wave,
+    K: Wave,
+    L: Wave,
+{
+    const STATE_LEN: usize =
+        I::STATE_LEN + J::STATE_LEN + K::STATE_LEN + L::STATE_LEN;
+    const PARAMS: usize =
+        params(params(I::PARAMS, J::PARAMS), params(K::PARAMS, L::PARAMS));
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let duty = data.input(&self.1, I::STATE_LEN);
+        let zero = data.input(&self.2, I::STATE_LEN + J::STATE_LEN);
+        let peak =
+            data.input(&self.3, I::STATE_LEN + J::STATE_LEN + K::STATE_LEN);
+        let mut i = 0;
+
+        chunk
+            .for_each_sample(|sample| {
+                *sample = sine_phase(*sample, duty.0[i], zero.0[i], peak.0[i]);
+                i += 1;
+            })
+            .gain(consts::PI)
+            .cosine()
+            .invert()
+    }
+}
+
+/// Warp one sample of phase (-1 to 1) for a shaped sine wave (`-cos(πx)`)
+///
+/// Every step is exact when duty, zero and peak are 0, so the phase is
+/// unchanged.
+#[inline(always)]
+#[allow(clippy::manual_clamp)]
+pub(crate) fn sine_phase(phase: f32, duty: f32, zero: f32, peak: f32) -> f32 {
+    // Not `clamp()`, to keep NaN parameters from making NaN phases
+    let duty = duty.max(-1.0).min(1.0);
+    let zero = zero.max(-1.0).min(1.0);
+    let peak = peak.max(-1.0).min(1.0);
+    // Peaks are at 0 and 1, with the zero crossing between them
+    let crossing = (1.0 - duty) * 0.5;
+    let from_peak = |x: f32| x + x * (1.0 - x) * (peak * (1.0 - x) - zero * x);
+    let x = phase.abs();
+    let warped = if x < crossing {
+        from_peak(x / crossing) * 0.5
+    } else {
+        1.0 - from_peak(((1.0 - x) / (1.0 - crossing)).min(1.0)) * 0.5
+    };
+
+    libm::copysignf(warped, phase)
+}
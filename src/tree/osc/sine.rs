@@ -13,6 +13,7 @@ where
     I: Wave,
 {
     const STATE_LEN: usize = I::STATE_LEN;
+    const PARAMS: usize = I::PARAMS;
 
     fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
         self.0.synthesize(data).gain(consts::PI).cosine().invert()
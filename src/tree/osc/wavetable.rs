@@ -0,0 +1,57 @@
+use crate::{
+    osc,
+    tree::{consts, params, Chunk, Data, Wave},
+};
+
+/// Wavetable oscillator
+///
+/// Takes frequency (hertz), the [`osc::Wavetable`]s and morph position (0 is
+/// the first table, 1 is the last) as input; see [`osc::TableOsc`]
+///
+/// ```rust
+/// use fon::{chan::Ch16, Audio};
+/// use twang::{
+///     osc,
+///     tree::{line::Line, Synth},
+/// };
+///
+/// let sine: Vec<f32> = (0..256)
+///     .map(|i| (i as f32 / 256.0 * std::f32::consts::TAU).sin())
+///     .collect();
+/// let saw: Vec<f32> = (0..256).map(|i| 1.0 - i as f32 / 128.0).collect();
+/// let wavetable = osc::Wavetable::new(&[&sine, &saw]);
+/// // Halfway between a sine and a sawtooth
+/// let waveform = Line(440.0).wavetable(&wavetable, Line(0.5));
+/// let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 48_000 * 5);
+/// let mut synth = Synth::new(waveform, []);
+///
+/// synth.stream(audio.sink());
+/// ```
+#[derive(Debug)]
+pub struct Wavetable<'a, I, J>(pub I, pub &'a osc::Wavetable, pub J);
+
+impl<I, J> Wave for Wavetable<'_, I, J>
+where
+    I: Wave,
+    J: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN + J::STATE_LEN + 1;
+    const PARAMS: usize = params(I::PARAMS, J::PARAMS);
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let period = data.chunk_step * consts::FRAC_32[1];
+        let position = data.input(&self.2, 1 + I::STATE_LEN);
+        let mut phase = f32::from_bits(data.state[0]);
+        let mut i = 0;
+        let chunk = data.input(&self.0, 1).for_each_sample(|sample| {
+            let dt = *sample * period;
+
+            *sample = self.1.sample(phase, dt, position.0[i]);
+            phase = (phase + dt).rem_euclid(1.0);
+            i += 1;
+        });
+
+        data.state[0] = phase.to_bits();
+        chunk
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    osc::{self, Transition, Waypoint},
+    tree::{Chunk, Data, Wave},
+};
+
+/// Waypoint (arbitrary waveform) oscillator
+///
+/// Takes phase (-1 to 1) as input, followed by the waypoints (sorted by phase
+/// in cycles) and how to move between them; see [`osc::Waypoints`]
+///
+/// ```rust
+/// use fon::{chan::Ch16, Audio};
+/// use twang::{
+///     osc::{Transition, Waypoint},
+///     tree::{line::Line, Synth},
+/// };
+///
+/// const STEPS: &[Waypoint] = &[
+///     Waypoint::new(0.0, 1.0),
+///     Waypoint::new(0.25, 0.5),
+///     Waypoint::new(0.5, -0.5),
+///     Waypoint::new(0.75, -1.0),
+/// ];
+///
+/// let waveform =
+///     const { Line(220.0).osc().waypoints(STEPS, Transition::Jump) };
+/// let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 48_000 * 5);
+/// let mut synth = Synth::new(waveform, []);
+///
+/// synth.stream(audio.sink());
+/// ```
+#[derive(Debug)]
+pub struct Waypoints<'a, I>(pub I, pub &'a [Waypoint], pub Transition);
+
+impl<I> Wave for Waypoints<'_, I>
+where
+    I: Wave,
+{
+    const STATE_LEN: usize = I::STATE_LEN;
+    const PARAMS: usize = I::PARAMS;
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        debug_assert!(osc::sorted(self.1), "Waypoints must be sorted by phase");
+        self.0.synthesize(data).for_each_sample(|sample| {
+            // Convert to 0 to 1 cycle
+            let phase = (1.0 - *sample) * 0.5;
+            let phase = phase - libm::floorf(phase);
+
+            *sample = osc::waypoints(self.1, self.2, phase);
+        })
+    }
+}
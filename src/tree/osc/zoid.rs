@@ -0,0 +1,69 @@
+use crate::tree::{params, Chunk, Data, Wave};
+
+/// Trapezoid wave
+///
+/// Takes phase (-1 to 1), and the locations in the cycle (-1 at the start to 1
+/// at the end) to start the rise (from -1 to 1), hold (at 1) and fall (from 1
+/// to -1) as input.  Before the rise, the wave holds at -1.
+///
+/// Each location must be larger than the previous (earlier locations are
+/// moved up to the previous one), so that:
+///
+///  - `(-1, -1, -1)` is a downward sawtooth (the same as [`Osc`](super::Osc))
+///  - `(-1, 1, 1)` is an upward sawtooth
+///  - `(-1, -1, 1)` is a constant 1
+///  - `(-1, 0, 0)` is a triangle wave
+///  - `(0, 0, 1)` is a square wave
+///  - `(1, 1, 1)` is a constant -1
+#[derive(Debug)]
+pub struct Zoid<I, J, K, L>(pub I, pub J, pub K, pub L);
+
+impl<I, J, K, L> Wave for Zoid<I, J, K, L>
+where
+    I: Wave,
+    J: Wave,
+    K: Wave,
+    L: Wave,
+{
+    const STATE_LEN: usize =
+        I::STATE_LEN + J::STATE_LEN + K::STATE_LEN + L::STATE_LEN;
+    const PARAMS: usize =
+        params(params(I::PARAMS, J::PARAMS), params(K::PARAMS, L::PARAMS));
+
+    fn synthesize(&self, data: &mut Data<'_>) -> Chunk {
+        let chunk = data.input(&self.0, 0);
+        let rise = data.input(&self.1, I::STATE_LEN);
+        let hold = data.input(&self.2, I::STATE_LEN + J::STATE_LEN);
+        let fall =
+            data.input(&self.3, I::STATE_LEN + J::STATE_LEN + K::STATE_LEN);
+        let mut i = 0;
+
+        chunk.for_each_sample(|sample| {
+            // Phase starts each cycle at 1
+            *sample = zoid(-*sample, rise.0[i], hold.0[i], fall.0[i]);
+            i += 1;
+        })
+    }
+}
+
+/// Get one sample of a trapezoid wave at a location in the cycle (-1 at the
+/// start to 1 at the end)
+#[inline(always)]
+#[allow(clippy::manual_clamp)]
+pub(crate) fn zoid(location: f32, rise: f32, hold: f32, fall: f32) -> f32 {
+    // Not `clamp()`, which panics when a NaN location is a bound
+    let rise = rise.max(-1.0).min(1.0);
+    let hold = hold.max(rise).min(1.0);
+    let fall = fall.max(hold).min(1.0);
+
+    if location < rise {
+        -1.0
+    } else if location < hold {
+        (location - rise) / (hold - rise) * 2.0 - 1.0
+    } else if location < fall || fall >= 1.0 {
+        // A fall at the end of the cycle has no width to divide by
+        1.0
+    } else {
+        1.0 - (location - fall) / (1.0 - fall) * 2.0
+    }
+}
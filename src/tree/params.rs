@@ -41,11 +41,11 @@ impl<const N: usize> Parameters for Params<N> {
         buffer
             .iter_mut()
             .zip(
-                consts::FRAC_32
+                consts::FRAC_32_REV
                     .iter()
                     .cloned()
                     .map(|x| x * old)
-                    .zip(consts::FRAC_32_REV.iter().cloned().map(|x| x * new)),
+                    .zip(consts::FRAC_32.iter().cloned().map(|x| x * new)),
             )
             .for_each(|(buf, (old, new))| *buf = old + new);
 
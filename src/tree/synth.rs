@@ -1,8 +1,10 @@
-use alloc::{vec, vec::Vec};
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::marker::PhantomData;
 
 use fon::{
     chan::{Ch32, Channel},
-    Sink,
+    pos::Mono,
+    Audio, Sink,
 };
 
 use crate::tree::{consts, Chunk, Parameters, Params, Wave};
@@ -11,12 +13,38 @@ use crate::tree::{consts, Chunk, Parameters, Params, Wave};
 pub struct Data<'a> {
     /// Reference to state slice at index
     pub(crate) state: &'a mut [u32],
-    /// 1 hertz sample steps for chunk (0, 1/32, 2/32, etc.)
-    pub(crate) sample_steps: &'a [f32; 32],
     /// User parameters
     pub(crate) params: &'a mut dyn Parameters,
     /// 1 hertz chunk step (1; 32 samples)
     pub(crate) chunk_step: f32,
+    /// Audio fed into the synthesizer
+    pub(crate) input: &'a Chunk,
+}
+
+impl Data<'_> {
+    /// Synthesize an input waveform, with its states starting at `offset`
+    pub(crate) fn input(&mut self, wave: &impl Wave, offset: usize) -> Chunk {
+        wave.synthesize(&mut Data {
+            state: &mut self.state[offset..],
+            params: &mut *self.params,
+            chunk_step: self.chunk_step,
+            input: self.input,
+        })
+    }
+}
+
+/// Compile-time check that a waveform has enough parameters
+///
+/// Only evaluated when [`Synth::new()`] is monomorphized, which `cargo check`
+/// doesn't do.
+struct ParamsCheck<W, const N: usize>(PhantomData<W>);
+
+impl<W, const N: usize> ParamsCheck<W, N>
+where
+    W: Wave,
+{
+    const CHECK: () =
+        assert!(W::PARAMS <= N, "Not enough parameters for waveform");
 }
 
 /// A streaming synthesizer
@@ -30,6 +58,7 @@ where
     state: Vec<u32>,
     wave: W,
     params: Params<N>,
+    input: VecDeque<f32>,
 }
 
 impl<W, const N: usize> Synth<W, N>
@@ -37,13 +66,29 @@ where
     W: Wave,
 {
     /// Create a new synthesizer based on a waveform.
+    ///
+    /// The waveform must not use parameters for a synthesizer with more than
+    /// `N` parameters.  This is checked after monomorphization, so using too
+    /// many parameters fails `cargo build`, but passes `cargo check`.
+    ///
+    /// ```rust,compile_fail
+    /// use twang::tree::{line::Param, Synth};
+    ///
+    /// const PITCH: Param<2> = Param::new(1);
+    ///
+    /// // Only one parameter is provided
+    /// let synth = Synth::new(const { PITCH.osc().sine() }, [440.0]);
+    /// ```
     pub fn new(wave: W, params: [f32; N]) -> Self {
+        let () = ParamsCheck::<W, N>::CHECK;
+
         Self {
             wave,
             cursor: 32,
             chunk: Chunk([0.0; 32]),
             state: vec![0; W::STATE_LEN],
             params: Params::new(params),
+            input: VecDeque::new(),
         }
     }
 
@@ -52,6 +97,22 @@ where
         self.params.get_mut()
     }
 
+    /// Feed audio (at the sample rate of the stream) to be played by
+    /// [`line::Input`](crate::tree::line::Input), after any audio fed before.
+    ///
+    /// Audio with more channels is mixed down to mono.
+    pub fn feed<Ch, const CH: usize>(&mut self, audio: &Audio<Ch, CH>)
+    where
+        Ch: Channel,
+        Ch32: From<Ch>,
+    {
+        self.input.extend(
+            audio
+                .iter()
+                .map(|frame| frame.to::<Ch32, 1>()[Mono].to_f32()),
+        );
+    }
+
     /// Run synthesis with user parameters, streaming output into the provided
     /// [`Sink`].
     pub fn stream<Ch, const S: usize>(&mut self, mut sink: impl Sink<Ch, S>)
@@ -60,27 +121,26 @@ where
     {
         let chunk_step: f32 =
             (sink.sample_rate().get() as f32 * consts::FRAC_32[1]).recip();
-        let mut sample_steps = [chunk_step; 32];
-
-        sample_steps
-            .iter_mut()
-            .zip(consts::FRAC_32.iter())
-            .for_each(|(sample, &mul)| {
-                *sample *= mul;
-            });
-
-        let synth_iter = SynthIter(self, chunk_step, sample_steps);
+        let synth_iter = SynthIter(self, chunk_step);
 
         sink.sink_with(&mut synth_iter.map(|x| x.to()));
     }
 
-    fn synthesize(&mut self, chunk_step: f32, sample_steps: &[f32; 32]) -> f32 {
+    fn synthesize(&mut self, chunk_step: f32) -> f32 {
         if self.cursor == 32 {
+            let mut input = Chunk([0.0; 32]);
+            let len = self.input.len().min(32);
+
+            for (sample, fed) in input.0.iter_mut().zip(self.input.drain(..len))
+            {
+                *sample = fed;
+            }
+
             let mut data = Data {
                 state: self.state.as_mut_slice(),
                 params: &mut self.params,
-                sample_steps,
                 chunk_step,
+                input: &input,
             };
 
             self.cursor = 0;
@@ -95,7 +155,7 @@ where
     }
 }
 
-struct SynthIter<'a, W, const N: usize>(&'a mut Synth<W, N>, f32, [f32; 32])
+struct SynthIter<'a, W, const N: usize>(&'a mut Synth<W, N>, f32)
 where
     W: Wave;
 
@@ -106,8 +166,8 @@ where
     type Item = fon::Frame<Ch32, 1>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let Self(synth, chunk_step, sample_steps) = self;
+        let Self(synth, chunk_step) = self;
 
-        Some(synth.synthesize(*chunk_step, sample_steps).into())
+        Some(synth.synthesize(*chunk_step).into())
     }
 }